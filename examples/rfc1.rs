@@ -1,9 +1,9 @@
 use env_logger;
 use env_logger::Env;
-use librfc_rust::{value::Value, connection::Connection};
+use librfc_rust::{value::Value, connection::Connection, RfcError};
 use log::info;
 
-fn main() -> Result<(), String> {
+fn main() -> Result<(), RfcError> {
     env_logger::Builder::from_env(Env::default().default_filter_or("info"))
         .format_timestamp(None)
         .init();
@@ -1,6 +1,6 @@
 use std::io::{stdout, Write};
 
-use librfc_rust::{Connection, Value};
+use librfc_rust::{Connection, RfcError, Value};
 use env_logger;
 use env_logger::Env;
 use clap::Parser;
@@ -14,7 +14,7 @@ struct Args {
 }
 
 
-fn main() -> Result<(), String>{
+fn main() -> Result<(), RfcError>{
     let args = Args::parse();
     env_logger::Builder::from_env(Env::default()
                         .default_filter_or("info"))
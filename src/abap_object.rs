@@ -0,0 +1,161 @@
+use std::cell::RefCell;
+
+use log::trace;
+
+use crate::{
+    check, convert,
+    error::{AbapClassException, RfcError, RfcErrorInfo},
+    error_info,
+    librfc::{
+        RfcDescribeAbapObject, RfcGetAbapClassException, RfcGetAbapObject,
+        RfcGetClassAttributeDescByIndex, RfcGetClassAttributesCount, RfcGetClassName,
+        _RFC_CLASS_ATTRIBUTE_TYPE_RFC_CLASS_ATTRIBUTE_INSTANCE, RFC_ABAP_OBJECT_HANDLE,
+        RFC_CLASS_ATTRIBUTE_DESC, RFC_CLASS_DESC_HANDLE, RFC_FUNCTION_HANDLE, RFC_TYPE_DESC_HANDLE,
+    },
+    string::SapString,
+    zero, RfcType,
+};
+
+thread_local! {
+    /// The objects that are being read on this thread, outermost first. An object that
+    /// refers back to one of them is not read again.
+    static READING: RefCell<Vec<RFC_ABAP_OBJECT_HANDLE>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Creates a zero-initialized `RFC_CLASS_ATTRIBUTE_DESC` structure.
+fn class_attribute_description() -> RFC_CLASS_ATTRIBUTE_DESC {
+    RFC_CLASS_ATTRIBUTE_DESC {
+        name: zero(),
+        type_: 0,
+        nucLength: 0,
+        ucLength: 0,
+        decimals: 0,
+        typeDescHandle: 0 as RFC_TYPE_DESC_HANDLE,
+        defaultValue: zero(),
+        declaringClass: zero(),
+        description: zero(),
+        isReadOnly: 0,
+        attributeType: 0,
+        extendedDescription: 0 as *mut cty::c_void,
+    }
+}
+
+/// Reads the class-based exception that was raised by the last call of the function.
+/// The class name and the instance attributes of the exception object are read through
+/// the ABAP object APIs and converted into JSON values, so the result does not depend
+/// on the lifetime of the function handle.
+pub(crate) fn class_exception(
+    fh: RFC_FUNCTION_HANDLE,
+    info: RfcErrorInfo,
+) -> Result<AbapClassException, RfcError> {
+    let mut errorInfo = error_info();
+    let obj = unsafe { RfcGetAbapClassException(fh, &mut errorInfo) };
+    if obj.is_null() {
+        return Err(RfcError::from(errorInfo));
+    }
+    let (class_name, attributes) = read_object(obj)?;
+    Ok(AbapClassException {
+        class_name,
+        message: info.message,
+        attributes: attributes.unwrap_or_default(),
+    })
+}
+
+/// Reads an ABAP object as a JSON object with its `class` and its `attributes`.
+/// A reference back to an object that is still being read, e.g. from a child node to
+/// its parent, is written as `{"class": ..., "cyclic": true}` instead.
+pub(crate) fn object_to_json(
+    obj: RFC_ABAP_OBJECT_HANDLE,
+) -> Result<serde_json::Map<String, serde_json::Value>, RfcError> {
+    let (class_name, attributes) = read_object(obj)?;
+    let mut object = serde_json::Map::new();
+    object.insert("class".to_string(), class_name.into());
+    match attributes {
+        Some(attributes) => object.insert("attributes".to_string(), attributes.into()),
+        None => object.insert("cyclic".to_string(), true.into()),
+    };
+    Ok(object)
+}
+
+/// Reads the class name and all instance attributes of an ABAP object.
+/// The attributes are `None` if the object is already being read on this thread.
+fn read_object(
+    obj: RFC_ABAP_OBJECT_HANDLE,
+) -> Result<(String, Option<serde_json::Map<String, serde_json::Value>>), RfcError> {
+    let mut errorInfo = error_info();
+    let class_desc = unsafe { RfcDescribeAbapObject(obj, &mut errorInfo) };
+    if class_desc.is_null() {
+        return Err(RfcError::from(errorInfo));
+    }
+    let mut name_buffer: [u16; 31] = zero();
+    let rc = unsafe { RfcGetClassName(class_desc, name_buffer.as_mut_ptr(), &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    let class_name = String::from(&SapString::from(name_buffer.as_slice()));
+    if READING.with(|r| r.borrow().contains(&obj)) {
+        trace!("object of class {class_name} refers back to an object being read");
+        return Ok((class_name, None));
+    }
+    trace!("reading attributes of class {class_name}");
+
+    READING.with(|r| r.borrow_mut().push(obj));
+    let attributes = read_attributes(obj, class_desc);
+    READING.with(|r| r.borrow_mut().pop());
+    Ok((class_name, Some(attributes?)))
+}
+
+/// Reads all instance attributes of an ABAP object as JSON values.
+fn read_attributes(
+    obj: RFC_ABAP_OBJECT_HANDLE,
+    class_desc: RFC_CLASS_DESC_HANDLE,
+) -> Result<serde_json::Map<String, serde_json::Value>, RfcError> {
+    let mut errorInfo = error_info();
+    let mut count: cty::c_uint = 0;
+    let rc = unsafe { RfcGetClassAttributesCount(class_desc, &mut count, &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    let mut attributes = serde_json::Map::new();
+    for idx in 0..count {
+        let mut attrDesc = class_attribute_description();
+        let rc = unsafe {
+            RfcGetClassAttributeDescByIndex(class_desc, idx, &mut attrDesc, &mut errorInfo)
+        };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
+        }
+        if attrDesc.attributeType != _RFC_CLASS_ATTRIBUTE_TYPE_RFC_CLASS_ATTRIBUTE_INSTANCE {
+            continue;
+        }
+        let name = String::from(&SapString::from(attrDesc.name.as_slice()));
+        let value = read_attribute(obj, &name, &attrDesc)?;
+        attributes.insert(name, value);
+    }
+    Ok(attributes)
+}
+
+/// Reads a single attribute of an ABAP object as a JSON value.
+/// Object references are read as nested objects, all other types are read with the same
+/// conversions as function parameters.
+fn read_attribute(
+    obj: RFC_ABAP_OBJECT_HANDLE,
+    name: &str,
+    attrDesc: &RFC_CLASS_ATTRIBUTE_DESC,
+) -> Result<serde_json::Value, RfcError> {
+    match RfcType::try_from(attrDesc.type_)? {
+        RfcType::AbapObject => {
+            let sap_name = SapString::from(name);
+            let mut handle = 0 as RFC_ABAP_OBJECT_HANDLE;
+            check(|e| unsafe { RfcGetAbapObject(obj, sap_name.raw_pointer(), &mut handle, e) })?;
+            if handle.is_null() {
+                return Ok(serde_json::Value::Null);
+            }
+            object_to_json(handle).map(serde_json::Value::Object)
+        }
+        typ => {
+            let value = convert::get_value(obj, name, typ, attrDesc.nucLength)?;
+            serde_json::to_value(&value).map_err(|e| RfcError::conversion(name, e.to_string()))
+        }
+    }
+}
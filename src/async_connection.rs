@@ -0,0 +1,144 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use log::{trace, warn};
+
+use crate::{
+    any_to_error,
+    connection::{CancelHandle, Connection},
+    error::RfcError,
+};
+
+/// The call that is currently blocked in the SDK, identified by its number.
+type RunningCall = Mutex<Option<(u64, CancelHandle)>>;
+
+/// A connection for async code, available with the `async` feature.
+/// Every call runs on the blocking thread pool of the tokio runtime, so the SDK never
+/// blocks an async worker thread. Calls on the same connection are executed one after
+/// the other. If the future of a call is dropped, e.g. by a timeout or a `select!`,
+/// the running call is cancelled with `RfcCancel`; the SDK closes the connection in that
+/// case, and it is reopened by the next call.
+#[derive(Clone)]
+pub struct AsyncConnection {
+    connection: Arc<Mutex<Connection>>,
+    running: Arc<RunningCall>,
+    next_call: Arc<AtomicU64>,
+}
+
+impl AsyncConnection {
+    /// Connects `connection` on the blocking thread pool.
+    /// # Errors
+    /// * Returns the error of [`Connection::connect`].
+    pub async fn connect(connection: Connection) -> Result<Self, RfcError> {
+        let connection = tokio::task::spawn_blocking(move || connection.connect())
+            .await
+            .map_err(any_to_error)??;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            running: Arc::new(Mutex::new(None)),
+            next_call: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Calls a function module with the given parameters.
+    /// The parameters are set like with `Function::set_parameters`, i.e. `parameters` is a
+    /// JSON object with the parameter names as keys.
+    /// # Returns
+    /// * `Result<serde_json::Value, RfcError>` - All parameters of the function after the
+    ///   call, serialized the same way as a `Function`.
+    /// # Errors
+    /// * Returns the error of retrieving, preparing or executing the function.
+    pub async fn call(
+        &self,
+        name: &str,
+        parameters: serde_json::Value,
+    ) -> Result<serde_json::Value, RfcError> {
+        let id = self.next_call.fetch_add(1, Ordering::Relaxed);
+        let mut guard = CancelOnDrop {
+            id,
+            running: self.running.clone(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            armed: true,
+        };
+        let connection = self.connection.clone();
+        let running = self.running.clone();
+        let cancelled = guard.cancelled.clone();
+        let name = name.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            let mut c = connection.lock().map_err(any_to_error)?;
+            if cancelled.load(Ordering::Acquire) {
+                return Err(RfcError::Cancelled(None));
+            }
+            if !c.is_alive() {
+                trace!("reopening connection before calling {name}");
+                c.reopen()?;
+            }
+            let f = c.function(&name)?;
+            f.set_parameters(&parameters)?;
+            *running.lock().map_err(any_to_error)? = Some((id, c.cancel_handle()));
+            // the future may have been dropped before the call was registered
+            let result = if cancelled.load(Ordering::Acquire) {
+                Err(RfcError::Cancelled(None))
+            } else {
+                f.execute()
+            };
+            *running.lock().map_err(any_to_error)? = None;
+            result?;
+            serde_json::to_value(&f).map_err(|e| RfcError::Internal(e.to_string()))
+        })
+        .await
+        .map_err(any_to_error)?;
+        guard.armed = false;
+        result
+    }
+
+    /// Calls a function module like [`AsyncConnection::call`], but gives up after `timeout`.
+    /// The running call is cancelled when the timeout expires.
+    /// # Errors
+    /// * Returns `RfcError::TimedOut` if the call did not finish in time, or the error
+    ///   of the call.
+    pub async fn call_with_timeout(
+        &self,
+        name: &str,
+        parameters: serde_json::Value,
+        timeout: Duration,
+    ) -> Result<serde_json::Value, RfcError> {
+        tokio::time::timeout(timeout, self.call(name, parameters))
+            .await
+            .map_err(|_| RfcError::TimedOut(timeout))?
+    }
+}
+
+/// Cancels the call with the given number if its future is dropped before it finished.
+struct CancelOnDrop {
+    id: u64,
+    running: Arc<RunningCall>,
+    cancelled: Arc<AtomicBool>,
+    armed: bool,
+}
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        // a call that has not started yet must not start anymore
+        self.cancelled.store(true, Ordering::Release);
+        // keep the lock while cancelling, so our call cannot be replaced by the next one
+        if let Ok(running) = self.running.lock() {
+            if let Some((id, handle)) = running.as_ref() {
+                if *id == self.id {
+                    warn!("cancelling running call");
+                    if let Err(e) = handle.cancel() {
+                        warn!("cancel failed: {e}");
+                    }
+                }
+            }
+        }
+    }
+}
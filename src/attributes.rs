@@ -0,0 +1,99 @@
+use serde::Serialize;
+
+use crate::{librfc::RFC_ATTRIBUTES, string::SapString, zero};
+
+/// Details about an open connection and the SAP system it is connected to,
+/// as returned by `RfcGetConnectionAttributes`.
+/// With a load-balanced logon this shows the application server that was actually chosen.
+/// Single character codes are kept as returned by the SDK, e.g. `rfc_role` is `C` for an
+/// RFC client and `partner_type` is `3` for an ABAP system.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConnectionAttributes {
+    pub dest: String,
+    pub host: String,
+    pub partner_host: String,
+    pub sys_number: String,
+    pub sys_id: String,
+    pub client: String,
+    pub user: String,
+    pub language: String,
+    pub iso_language: String,
+    pub trace: String,
+    pub codepage: String,
+    pub partner_codepage: String,
+    pub partner_system_codepage: String,
+    pub partner_bytes_per_char: String,
+    pub rfc_role: String,
+    pub r#type: String,
+    pub partner_type: String,
+    pub rel: String,
+    pub partner_rel: String,
+    pub kernel_rel: String,
+    pub cpic_conv_id: String,
+    pub prog_name: String,
+    pub partner_ip: String,
+    pub partner_ipv6: String,
+}
+
+impl From<&RFC_ATTRIBUTES> for ConnectionAttributes {
+    fn from(value: &RFC_ATTRIBUTES) -> Self {
+        let s = |x: &[u16]| String::from(&SapString::from(x)).trim_end().to_string();
+        Self {
+            dest: s(value.dest.as_slice()),
+            host: s(value.host.as_slice()),
+            partner_host: s(value.partnerHost.as_slice()),
+            sys_number: s(value.sysNumber.as_slice()),
+            sys_id: s(value.sysId.as_slice()),
+            client: s(value.client.as_slice()),
+            user: s(value.user.as_slice()),
+            language: s(value.language.as_slice()),
+            iso_language: s(value.isoLanguage.as_slice()),
+            trace: s(value.trace.as_slice()),
+            codepage: s(value.codepage.as_slice()),
+            partner_codepage: s(value.partnerCodepage.as_slice()),
+            partner_system_codepage: s(value.partnerSystemCodepage.as_slice()),
+            partner_bytes_per_char: s(value.partnerBytesPerChar.as_slice()),
+            rfc_role: s(value.rfcRole.as_slice()),
+            r#type: s(value.type_.as_slice()),
+            partner_type: s(value.partnerType.as_slice()),
+            rel: s(value.rel.as_slice()),
+            partner_rel: s(value.partnerRel.as_slice()),
+            kernel_rel: s(value.kernelRel.as_slice()),
+            cpic_conv_id: s(value.cpicConvId.as_slice()),
+            prog_name: s(value.progName.as_slice()),
+            partner_ip: s(value.partnerIP.as_slice()),
+            partner_ipv6: s(value.partnerIPv6.as_slice()),
+        }
+    }
+}
+
+/// Creates a zero-initialized `RFC_ATTRIBUTES` structure.
+pub(crate) fn rfc_attributes() -> RFC_ATTRIBUTES {
+    RFC_ATTRIBUTES {
+        dest: zero(),
+        host: zero(),
+        partnerHost: zero(),
+        sysNumber: zero(),
+        sysId: zero(),
+        client: zero(),
+        user: zero(),
+        language: zero(),
+        trace: zero(),
+        isoLanguage: zero(),
+        codepage: zero(),
+        partnerCodepage: zero(),
+        rfcRole: zero(),
+        type_: zero(),
+        partnerType: zero(),
+        rel: zero(),
+        partnerRel: zero(),
+        kernelRel: zero(),
+        cpicConvId: zero(),
+        progName: zero(),
+        partnerBytesPerChar: zero(),
+        partnerSystemCodepage: zero(),
+        partnerIP: zero(),
+        partnerIPv6: zero(),
+        reserved: zero(),
+    }
+}
@@ -1,15 +1,126 @@
+use std::{path::PathBuf, time::Duration};
+
 use clap::Parser;
 use env_logger::Env;
-use librfc_rust::connection::Connection;
-use log::{info, trace};
+use librfc_rust::{
+    attributes::ConnectionAttributes,
+    config::Profiles,
+    connection::Connection,
+    ini::IniFile,
+    sdk::{SdkConfig, TraceType},
+    value::{BytesEncoding, SerializeWith, WithBytesEncoding},
+    Function,
+};
+use log::{info, trace, warn};
+use serde::Serialize;
 
 #[derive(Debug, Parser)]
+#[command(disable_version_flag = true)]
 struct CommandLineArgs {
-    pub rfc_destination: String,
+    /// RFC destination in sapnwrfc.ini, or the system name if a profile file is given
+    #[arg(required_unless_present_any = ["list", "version"])]
+    pub rfc_destination: Option<String>,
     pub script_name: Option<String>,
+    /// YAML or TOML profile file with named systems
+    #[arg(short, long)]
+    pub profiles: Option<PathBuf>,
+    /// Cancel a function call that takes longer than the given number of seconds
+    #[arg(short, long)]
+    pub timeout: Option<u64>,
+    /// Wrap each result with metadata about the connection and the called function
+    #[arg(short, long)]
+    pub metadata: bool,
+    /// List the destinations in sapnwrfc.ini with masked passwords and exit
+    #[arg(short, long)]
+    pub list: bool,
+    /// Print the version of this program and of the loaded SAP NW RFC SDK and exit
+    #[arg(short = 'V', long)]
+    pub version: bool,
+    /// Read and write RAW and XSTRING values as hexadecimal instead of base64
+    #[arg(long)]
+    pub hex: bool,
+    /// SDK trace level from 0 (off) to 4 (full)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(0..=4))]
+    pub trace_level: Option<u8>,
+    /// Directory for the SDK trace files, it has to exist
+    #[arg(long)]
+    pub trace_dir: Option<PathBuf>,
+    /// Write one SDK trace file per thread instead of one per process
+    #[arg(long)]
+    pub trace_per_thread: bool,
+    /// Size in megabytes after which an SDK trace file is rolled over
+    #[arg(long)]
+    pub trace_max_file_size: Option<u32>,
+    /// Seconds the logon in the SAP system may take
+    #[arg(long)]
+    pub logon_timeout: Option<u64>,
+    /// Seconds between CPIC keepalive packets, 0 turns them off
+    #[arg(long)]
+    pub cpic_keepalive: Option<u64>,
+}
+
+impl CommandLineArgs {
+    /// Returns the SDK settings given on the command line.
+    fn sdk_config(&self) -> SdkConfig {
+        let mut config = SdkConfig::default();
+        if let Some(level) = self.trace_level {
+            config = config.trace_level(level);
+        }
+        if let Some(dir) = &self.trace_dir {
+            config = config.trace_dir(dir);
+        }
+        if self.trace_per_thread {
+            config = config.trace_type(TraceType::Thread);
+        }
+        if let Some(size) = self.trace_max_file_size {
+            config = config.max_trace_file_size(size);
+        }
+        if let Some(secs) = self.logon_timeout {
+            config = config.logon_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = self.cpic_keepalive {
+            config = config.cpic_keepalive(Duration::from_secs(secs));
+        }
+        config
+    }
+}
+
+/// Information about the call that is written together with the result.
+#[derive(Serialize)]
+struct Metadata<'a> {
+    function_name: &'a str,
+    connection: &'a ConnectionAttributes,
+}
+
+/// The result of one call, wrapped with its metadata when `--metadata` is given.
+#[derive(Serialize)]
+struct CallResult<'a> {
+    metadata: Metadata<'a>,
+    result: WithBytesEncoding<'a, Function<'a>>,
+}
+
+/// Prints all destinations of the sapnwrfc.ini file as JSON and warns about incomplete ones.
+fn list_destinations() -> Result<(), Box<dyn std::error::Error>> {
+    let path = IniFile::locate().ok_or("sapnwrfc.ini not found, set RFC_INI to its directory")?;
+    let ini = IniFile::load(&path)?;
+    for d in ini.destinations.iter() {
+        if let Err(e) = d.validate() {
+            warn!("{e}");
+        }
+    }
+    let masked: Vec<_> = ini.destinations.iter().map(|d| d.masked()).collect();
+    serde_json::to_writer_pretty(std::io::stdout(), &masked)?;
+    Ok(())
 }
 
-fn main() -> Result<(), String> {
+/// Prints the version of this program and of the SDK library it has loaded.
+fn print_version() {
+    let sdk = librfc_rust::sdk_version();
+    println!("{} {}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
+    println!("SAP NW RFC SDK {sdk} ({})", sdk.version);
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     // let args = CommandLineArgs {
     // rfc_destination: "sap".to_string(),
     // function_name: "RFC_SYSTEM_INFO".to_string(),
@@ -42,6 +153,20 @@ fn main() -> Result<(), String> {
         .format_timestamp(None)
         .init();
 
+    if args.version {
+        print_version();
+        return Ok(());
+    }
+    if args.list {
+        return list_destinations();
+    }
+    let rfc_destination = args.rfc_destination.as_deref().unwrap_or_default();
+    let encoding = if args.hex {
+        BytesEncoding::Hex
+    } else {
+        BytesEncoding::Base64
+    };
+
     let script: serde_json::Value = if let Some(script_name) = &args.script_name {
         serde_yaml::from_reader(
             std::fs::File::open(script_name)
@@ -53,12 +178,39 @@ fn main() -> Result<(), String> {
     .map_err(|e| e.to_string())?;
 
     if let serde_json::Value::Array(steps) = script {
+        args.sdk_config().apply()?;
         info!("connecting");
-        let c = Connection::new()
-            .destination(&args.rfc_destination)
-            .connect()?;
+        let c = match &args.profiles {
+            Some(path) => Profiles::load(path)?.get(rfc_destination)?.connection()?,
+            None => {
+                // catch misspelled destinations before the SDK reports a logon failure,
+                // the SDK itself is more lenient in reading the file
+                if let Some(path) = IniFile::locate() {
+                    match IniFile::load(path) {
+                        Ok(ini) => {
+                            ini.destination(rfc_destination)?;
+                        }
+                        Err(e) => warn!("not checking the destination: {e}"),
+                    }
+                }
+                Connection::new().destination(rfc_destination)
+            }
+        }
+        .connect()?;
 
-        assert!(c.is_connected());
+        if !c.is_connected() {
+            return Err(format!("connection to {rfc_destination} is not open").into());
+        }
+        let attributes = if args.metadata {
+            let attributes = c.attributes()?;
+            info!(
+                "connected to {} client {} on {} as {}",
+                attributes.sys_id, attributes.client, attributes.partner_host, attributes.user
+            );
+            Some(attributes)
+        } else {
+            None
+        };
 
         for step in steps {
             let function_name = step
@@ -68,11 +220,27 @@ fn main() -> Result<(), String> {
             info!("calling function {function_name}");
             let f = c.function(function_name)?;
             if let Some(p) = step.get("parameters") {
-                f.set_parameters(&p)?;
+                f.set_parameters_with(p, encoding)?;
+            }
+            match args.timeout {
+                Some(secs) => f.execute_with_timeout(Duration::from_secs(secs))?,
+                None => f.execute()?,
             }
-            f.execute()?;
             trace!("serializing result");
-            serde_json::to_writer(std::io::stdout(), &f).map_err(|x| x.to_string())?;
+            match &attributes {
+                Some(attributes) => serde_json::to_writer(
+                    std::io::stdout(),
+                    &CallResult {
+                        metadata: Metadata {
+                            function_name,
+                            connection: attributes,
+                        },
+                        result: f.with_bytes_encoding(encoding),
+                    },
+                ),
+                None => serde_json::to_writer(std::io::stdout(), &f.with_bytes_encoding(encoding)),
+            }
+            .map_err(|x| x.to_string())?;
             trace!("serializing result done");
         }
     }
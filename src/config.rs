@@ -0,0 +1,276 @@
+use std::{collections::BTreeMap, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    connection::Connection,
+    error::RfcError,
+    rfc_param::{is_secret_name, RfcParam},
+    secret::{Secret, SecretSource},
+};
+
+/// Connection parameters of one SAP system as stored in a profile file.
+/// Every field maps to the SDK connection parameter of the same name; parameters without
+/// a dedicated field can be given in `params`. A system can name another system in
+/// `extends` to inherit all parameters it does not set itself.
+/// The password can be read from the environment, a file or a helper command,
+/// see [`SecretSource`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dest: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ashost: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sysnr: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mshost: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msserv: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sysid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub saprouter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wshost: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wsport: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passwd: Option<SecretSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub codepage: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl ConnectionConfig {
+    /// Fills all parameters that are not set in `self` from `parent`.
+    fn inherit(mut self, parent: &ConnectionConfig) -> Self {
+        macro_rules! inherit {
+            ($($field:ident),*) => {
+                $(if self.$field.is_none() {
+                    self.$field = parent.$field.clone();
+                })*
+            };
+        }
+        inherit!(
+            dest, ashost, sysnr, mshost, msserv, sysid, group, saprouter, wshost, wsport, client,
+            user, passwd, lang, trace, codepage
+        );
+        for (name, value) in parent.params.iter() {
+            self.params
+                .entry(name.clone())
+                .or_insert_with(|| value.clone());
+        }
+        self
+    }
+
+    /// Replaces `${NAME}` references to environment variables in all string parameters
+    /// and reads the password from its source. `$${` is kept as a literal `${`.
+    fn interpolate(mut self) -> Result<Self, RfcError> {
+        macro_rules! interpolate {
+            ($($field:ident),*) => {
+                $(if let Some(v) = &self.$field {
+                    self.$field = Some(interpolate_env(stringify!($field), v)?);
+                })*
+            };
+        }
+        interpolate!(
+            dest, ashost, sysnr, mshost, msserv, sysid, group, saprouter, wshost, wsport, client,
+            user, lang, codepage
+        );
+        self.passwd = match self.passwd.take() {
+            Some(SecretSource::Value(s)) => Some(SecretSource::Value(Secret::new(
+                interpolate_env("passwd", s.expose())?,
+            ))),
+            Some(source) => Some(SecretSource::Value(source.resolve()?)),
+            None => None,
+        };
+        for (name, value) in self.params.iter_mut() {
+            *value = interpolate_env(name, value)?;
+        }
+        Ok(self)
+    }
+
+    /// Returns the connection parameters in the form expected by `RfcOpenConnection`.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the password has to be read from a source that
+    ///   is not available.
+    pub fn to_params(&self) -> Result<Vec<RfcParam>, RfcError> {
+        let mut v = Vec::new();
+        let mut push = |name: &str, value: &Option<String>| {
+            if let Some(value) = value {
+                v.push(RfcParam::new(name, value.as_str()));
+            }
+        };
+        push("dest", &self.dest);
+        push("ashost", &self.ashost);
+        push("sysnr", &self.sysnr);
+        push("mshost", &self.mshost);
+        push("msserv", &self.msserv);
+        push("sysid", &self.sysid);
+        push("group", &self.group);
+        push("saprouter", &self.saprouter);
+        push("wshost", &self.wshost);
+        push("wsport", &self.wsport);
+        push("client", &self.client);
+        push("user", &self.user);
+        push("lang", &self.lang);
+        push("trace", &self.trace.map(|t| t.to_string()));
+        push("codepage", &self.codepage);
+        if let Some(passwd) = &self.passwd {
+            v.push(RfcParam::secret("passwd", &passwd.resolve()?));
+        }
+        for (name, value) in self.params.iter() {
+            v.push(RfcParam::new(name.as_str(), value.as_str()));
+        }
+        Ok(v)
+    }
+
+    /// Creates a not yet connected `Connection` from the parameters of this configuration.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the password cannot be read, see [`ConnectionConfig::to_params`].
+    pub fn connection(&self) -> Result<Connection, RfcError> {
+        Ok(self
+            .to_params()?
+            .into_iter()
+            .fold(Connection::new(), |c, p| c.param(p)))
+    }
+}
+
+/// A profile file with named SAP systems.
+/// The parameters in `defaults` apply to all systems, e.g. a common client or language.
+///
+/// ```yaml
+/// defaults:
+///   client: "100"
+///   lang: EN
+/// systems:
+///   dev:
+///     ashost: sapdev.example.com
+///     sysnr: "00"
+///     user: ${SAP_USER}
+///     passwd: { env: SAP_PASSWORD }
+///   qas:
+///     extends: dev
+///     ashost: sapqas.example.com
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profiles {
+    #[serde(default)]
+    pub defaults: ConnectionConfig,
+    #[serde(default)]
+    pub systems: BTreeMap<String, ConnectionConfig>,
+}
+
+impl Profiles {
+    /// Loads a profile file. Files ending in `.toml` are read as TOML, all others as YAML.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RfcError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| RfcError::Config(format!("{}: {e}", path.display())))?;
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            Self::from_toml(&content)
+        } else {
+            Self::from_yaml(&content)
+        }
+    }
+
+    /// Parses profiles from a YAML document.
+    pub fn from_yaml(s: &str) -> Result<Self, RfcError> {
+        serde_yaml::from_str(s).map_err(|e| RfcError::Config(e.to_string()))
+    }
+
+    /// Parses profiles from a TOML document.
+    pub fn from_toml(s: &str) -> Result<Self, RfcError> {
+        toml::from_str(s).map_err(|e| RfcError::Config(e.to_string()))
+    }
+
+    /// Returns the names of all systems in the profile file.
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.systems.keys()
+    }
+
+    /// Returns the fully resolved configuration of the named system.
+    /// Parameters are inherited along the `extends` chain and from `defaults`, then
+    /// environment variables are substituted and the password is read from its source.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the system is unknown, the `extends` chain is cyclic
+    ///   or refers to an unknown system, a referenced environment variable is not set, or
+    ///   the password source is not available.
+    pub fn get(&self, name: &str) -> Result<ConnectionConfig, RfcError> {
+        let mut chain: Vec<&str> = vec![];
+        let mut current = name;
+        let mut config = ConnectionConfig::default();
+        loop {
+            if chain.contains(&current) {
+                return Err(RfcError::Config(format!(
+                    "cyclic 'extends' in profile '{name}': {} -> {current}",
+                    chain.join(" -> ")
+                )));
+            }
+            let system = self
+                .systems
+                .get(current)
+                .ok_or_else(|| RfcError::Config(format!("unknown profile '{current}'")))?;
+            config = config.inherit(system);
+            chain.push(current);
+            match &system.extends {
+                Some(parent) => current = parent.as_str(),
+                None => break,
+            }
+        }
+        config.extends = None;
+        config.inherit(&self.defaults).interpolate()
+    }
+}
+
+/// Replaces all `${NAME}` references in the parameter `key` with the value of the
+/// environment variable `NAME`, `$${` is replaced with a literal `${`.
+/// Errors never contain the value, as it may be a password.
+fn interpolate_env(key: &str, value: &str) -> Result<String, RfcError> {
+    let mut result = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            result.push_str(&rest[..start]);
+            result.push('{');
+            rest = &rest[start + 2..];
+            continue;
+        }
+        result.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| RfcError::Config(format!("unterminated '${{' in parameter {key}")))?;
+        let var = &rest[start + 2..start + end];
+        let var_value = std::env::var(var).map_err(|_| {
+            // in a password the reference may as well be a part of the password itself
+            let var = if is_secret_name(key) {
+                String::new()
+            } else {
+                format!(" '{var}'")
+            };
+            RfcError::Config(format!(
+                "environment variable{var} referenced by parameter {key} is not set"
+            ))
+        })?;
+        result.push_str(&var_value);
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
@@ -1,17 +1,78 @@
+use std::sync::{Arc, Mutex};
+
 use log::trace;
+use zeroize::Zeroize;
 
 use crate::{
-    any_to_string, error_info, function::Function, librfc::RfcCloseConnection,
-    librfc::RfcCreateFunction, librfc::RfcGetFunctionDesc, librfc::RfcOpenConnection,
-    librfc::RfcSetIniPath, librfc::RFC_CONNECTION_HANDLE, librfc::_RFC_CONNECTION_HANDLE,
-    librfc::_RFC_CONNECTION_PARAMETER, rfc_param::RfcParam, string::SapString, CONNECT_COUNT,
-    INI_PATH_INITIALIZED,
+    any_to_error,
+    attributes::{rfc_attributes, ConnectionAttributes},
+    credentials::{Credentials, CREDENTIAL_PARAMS},
+    error::RfcError,
+    error_info,
+    function::Function,
+    librfc::{
+        RfcCancel, RfcCloseConnection, RfcCreateFunction, RfcGetConnectionAttributes,
+        RfcGetFunctionDesc, RfcGetPartnerSSOTicket, RfcIsConnectionHandleValid, RfcOpenConnection,
+        RfcPing, RfcResetServerContext, _RFC_CONNECTION_HANDLE, _RFC_CONNECTION_PARAMETER,
+        _RFC_RC_RFC_BUFFER_TOO_SMALL, RFC_CONNECTION_HANDLE,
+    },
+    rfc_param::RfcParam,
+    secret::Secret,
+    session::Session,
+    snc::SncConfig,
+    string::SapString,
+    websocket::WebsocketConfig,
+    CONNECT_COUNT,
 };
 
+/// Connection handle shared between a `Connection` and its `CancelHandle`s.
+/// It is reset to null under the lock before the connection is closed.
+struct SharedHandle(RFC_CONNECTION_HANDLE);
+
+// The handle is only used under the lock of the surrounding mutex.
+unsafe impl Send for SharedHandle {}
+
+/// Handle to cancel the call that is currently running on a connection.
+/// It can be cloned and sent to other threads, e.g. a watchdog that cancels calls that
+/// take too long. Cancelling a call makes the SDK close the connection; use
+/// [`Connection::reopen`] to continue with a new session.
+#[derive(Clone)]
+pub struct CancelHandle {
+    handle: Arc<Mutex<SharedHandle>>,
+}
+
+impl CancelHandle {
+    /// Cancels the call that is currently running on the connection.
+    /// The blocked `Function::execute` returns `RfcError::Cancelled`.
+    /// # Errors
+    /// * Returns `RfcError::Internal` if the connection is already closed, or the SDK error
+    ///   if the call could not be cancelled, e.g. because no call is running.
+    pub fn cancel(&self) -> Result<(), RfcError> {
+        let handle = self.handle.lock().map_err(any_to_error)?;
+        if handle.0.is_null() {
+            return Err(RfcError::Internal("connection is closed".to_string()));
+        }
+        let mut errorInfo = error_info();
+        let rc = unsafe { RfcCancel(handle.0, &mut errorInfo) };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
+        }
+        Ok(())
+    }
+}
+
 pub struct Connection {
     cn: RFC_CONNECTION_HANDLE,
     params: Vec<RfcParam>,
+    shared: Arc<Mutex<SharedHandle>>,
 }
+
+// The SDK allows a connection handle to be used by any thread, as long as it is not used
+// by two threads at the same time. Every `Function` borrows the `Connection` it was created
+// from, so a connection can only be moved to another thread when no function of it is left,
+// and `CancelHandle`s only use the handle under the lock of `SharedHandle`.
+unsafe impl Send for Connection {}
+
 impl Connection {
     /// Creates a new `Connection` instance with an empty parameter list and a connection handle set to zero.
     /// This function initializes the `Connection` struct with an empty vector for parameters
@@ -20,6 +81,7 @@ impl Connection {
         Self {
             params: Vec::new(),
             cn: 0 as RFC_CONNECTION_HANDLE,
+            shared: Arc::new(Mutex::new(SharedHandle(0 as RFC_CONNECTION_HANDLE))),
         }
     }
 
@@ -38,11 +100,235 @@ impl Connection {
     /// # Arguments
     /// * `name` - A string slice representing the name of the parameter.
     /// * `value` - A string slice representing the value of the parameter.
-    pub fn destination(mut self, arg: &str) -> Self {
-        self.params.push(RfcParam::new("dest", arg));
+    pub fn destination(self, arg: &str) -> Self {
+        self.with_param("dest", arg)
+    }
+
+    /// Sets the application server host for a direct logon (`ashost`).
+    /// Requires the system number to be set as well.
+    pub fn ashost(self, arg: &str) -> Self {
+        self.with_param("ashost", arg)
+    }
+
+    /// Sets the system number of the application server (`sysnr`), e.g. `00`.
+    pub fn sysnr(self, arg: &str) -> Self {
+        self.with_param("sysnr", arg)
+    }
+
+    /// Sets the client to log on to (`client`), e.g. `100`.
+    pub fn client(self, arg: &str) -> Self {
+        self.with_param("client", arg)
+    }
+
+    /// Sets the logon user (`user`).
+    pub fn user(self, arg: &str) -> Self {
+        self.with_param("user", arg)
+    }
+
+    /// Sets the logon password (`passwd`).
+    /// The password is kept as a [`Secret`]: it is wiped from memory when the connection
+    /// is dropped and never shows up in debug output.
+    pub fn passwd<S: Into<Secret>>(self, arg: S) -> Self {
+        self.param(RfcParam::secret("passwd", &arg.into()))
+    }
+
+    /// Sets the logon language (`lang`), e.g. `EN` or `DE`.
+    pub fn lang(self, arg: &str) -> Self {
+        self.with_param("lang", arg)
+    }
+
+    /// Sets the message server host for a load-balanced logon (`mshost`).
+    /// Requires either the system ID or the message server service to be set as well.
+    pub fn mshost(self, arg: &str) -> Self {
+        self.with_param("mshost", arg)
+    }
+
+    /// Sets the message server service or port (`msserv`), e.g. `3600`.
+    pub fn msserv(self, arg: &str) -> Self {
+        self.with_param("msserv", arg)
+    }
+
+    /// Sets the system ID used to look up the message server service (`sysid`).
+    pub fn sysid(self, arg: &str) -> Self {
+        self.with_param("sysid", arg)
+    }
+
+    /// Sets the logon group for a load-balanced logon (`group`), defaults to `PUBLIC`.
+    pub fn group(self, arg: &str) -> Self {
+        self.with_param("group", arg)
+    }
+
+    /// Sets the SAProuter string used to reach the system (`saprouter`).
+    pub fn saprouter(self, arg: &str) -> Self {
+        self.with_param("saprouter", arg)
+    }
+
+    /// Sets the RFC trace level of this connection (`trace`), from 0 (off) to 4 (full).
+    pub fn trace(self, level: u8) -> Self {
+        self.with_param("trace", level.to_string().as_str())
+    }
+
+    /// Sets the codepage used for the logon (`codepage`), e.g. `4110`.
+    pub fn codepage(self, arg: &str) -> Self {
+        self.with_param("codepage", arg)
+    }
+
+    /// Enables Secure Network Communication with the given settings.
+    /// Sets `snc_mode` and all SNC parameters of the configuration.
+    pub fn snc(self, config: SncConfig) -> Self {
+        config.to_params().into_iter().fold(self, |c, p| c.param(p))
+    }
+
+    /// Connects over WebSocket RFC instead of the gateway, with the given host, port and
+    /// TLS settings. The keep alive ping is set for the whole process, see
+    /// [`crate::websocket::set_ping_interval`].
+    pub fn websocket(self, config: WebsocketConfig) -> Self {
+        config.to_params().into_iter().fold(self, |c, p| c.param(p))
+    }
+
+    /// Sets the X.509 certificate used for the logon instead of user and password (`x509cert`).
+    /// The certificate is given in base64 encoded form without header and footer.
+    /// Requires SNC to be enabled, see [`Connection::snc`].
+    pub fn x509cert(self, arg: &str) -> Self {
+        self.with_param("x509cert", arg)
+    }
+
+    /// Sets an SAP logon ticket or an assertion ticket for the logon instead of user and
+    /// password (`mysapsso2`). The ticket is kept as a [`Secret`].
+    pub fn mysapsso2<S: Into<Secret>>(self, ticket: S) -> Self {
+        self.param(RfcParam::secret("mysapsso2", &ticket.into()))
+    }
+
+    /// Requests an SSO ticket for the logged on user during the logon (`getsso2`).
+    /// The ticket can be read with [`Connection::partner_sso_ticket`] afterwards.
+    pub fn request_sso_ticket(self, request: bool) -> Self {
+        self.with_param("getsso2", if request { "1" } else { "0" })
+    }
+
+    /// Sets the credentials for the logon and removes all credentials set before.
+    pub fn credentials(mut self, credentials: Credentials) -> Self {
+        self.params.retain(|p| {
+            let name = String::from(p.name());
+            !CREDENTIAL_PARAMS
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(&name))
+        });
+        credentials
+            .to_params()
+            .into_iter()
+            .fold(self, |c, p| c.param(p))
+    }
+
+    /// Adds an arbitrary connection parameter.
+    /// This can be used for any parameter of the SDK that has no dedicated builder method.
+    /// A parameter with the same name that was set before is replaced.
+    pub fn param(mut self, param: RfcParam) -> Self {
+        let name = String::from(param.name());
+        self.params
+            .retain(|p| !String::from(p.name()).eq_ignore_ascii_case(&name));
+        self.params.push(param);
         self
     }
 
+    fn with_param(self, name: &str, value: &str) -> Self {
+        self.param(RfcParam::new(name, value))
+    }
+
+    /// Returns the parameter with the given name, if it is set.
+    fn find_param(&self, name: &str) -> Option<&RfcParam> {
+        self.params
+            .iter()
+            .find(|p| String::from(p.name()).eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the value of the parameter with the given name, if it is set.
+    /// Confidential values are not returned, to avoid copies that are not wiped.
+    fn param_value(&self, name: &str) -> Option<String> {
+        self.find_param(name)
+            .filter(|p| !p.is_secret())
+            .map(|p| String::from(p.value()))
+    }
+
+    /// Checks that the connection parameters contain a complete set of logon information.
+    /// A connection either refers to a destination (`dest`) in the `sapnwrfc.ini` file,
+    /// or names an application server (`ashost` and `sysnr`), a message server
+    /// (`mshost` together with `msserv` or `sysid`) or a WebSocket RFC server
+    /// (`wshost` and `wsport`), plus the `client` and the credentials.
+    /// This check is done by `connect` before the SDK is called.
+    /// # Errors
+    /// * Returns `RfcError::Config` describing the first missing or conflicting parameter.
+    pub fn validate(&self) -> Result<(), RfcError> {
+        let has = |name: &str| self.find_param(name).is_some_and(|p| p.value().len() > 0);
+        if has("dest") {
+            return Ok(());
+        }
+        let missing = |name: &str, context: &str| {
+            Err(RfcError::Config(format!(
+                "parameter '{name}' is required for {context}"
+            )))
+        };
+        let servers: Vec<&str> = ["ashost", "mshost", "wshost"]
+            .into_iter()
+            .filter(|name| has(name))
+            .collect();
+        match servers.as_slice() {
+            ["ashost"] => {
+                if !has("sysnr") {
+                    return missing("sysnr", "an application server logon");
+                }
+            }
+            ["mshost"] => {
+                if !has("msserv") && !has("sysid") {
+                    return missing("msserv' or 'sysid", "a message server logon");
+                }
+            }
+            ["wshost"] => {
+                if !has("wsport") {
+                    return missing("wsport", "a WebSocket logon");
+                }
+            }
+            [] => {
+                return Err(RfcError::Config(
+                    "either 'dest', 'ashost', 'mshost' or 'wshost' has to be set".to_string(),
+                ))
+            }
+            _ => {
+                return Err(RfcError::Config(format!(
+                    "parameters '{}' cannot be combined",
+                    servers.join("' and '")
+                )))
+            }
+        }
+        let snc = self
+            .param_value("snc_mode")
+            .is_some_and(|v| v.trim() == "1");
+        if snc && !has("snc_partnername") {
+            return missing("snc_partnername", "an SNC connection");
+        }
+        if has("x509cert") && !snc {
+            return Err(RfcError::Config(
+                "a logon with 'x509cert' requires SNC, set 'snc_mode' to 1".to_string(),
+            ));
+        }
+        if !has("client") {
+            return missing("client", "a logon");
+        }
+        // the SDK enables SNC single sign-on by default
+        let snc_sso = snc && self.param_value("snc_sso").as_deref().map(str::trim) != Some("0");
+        if has("user") && !has("passwd") && !snc_sso {
+            return missing("passwd", "a logon with user");
+        }
+        let tls_logon = self
+            .param_value("tls_client_certificate_logon")
+            .is_some_and(|v| v.trim() == "1");
+        if !has("user") && !has("mysapsso2") && !has("x509cert") && !snc_sso && !tls_logon {
+            return Err(RfcError::Config(
+                "no credentials given, set 'user' and 'passwd'".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Returns a vector of parameter names for the connection.
     pub fn get_params(&self) -> Vec<SapString> {
         let mut v = Vec::new();
@@ -52,35 +338,154 @@ impl Connection {
         v
     }
 
+    /// Returns a handle to cancel a running call of this connection from another thread.
+    /// The handle stays valid when the connection is reopened, and cancelling
+    /// fails safely once the connection is closed.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle {
+            handle: self.shared.clone(),
+        }
+    }
+
+    /// Returns the connection parameters.
+    pub(crate) fn rfc_params(&self) -> &[RfcParam] {
+        &self.params
+    }
+
     /// Connects to the SAP system using the provided parameters.
     /// This function attempts to open a connection to the SAP system using the parameters
     /// specified in the connection. It increments the connection count and returns a `Result`
     /// indicating success or failure.
     /// # Returns
-    /// * `Result<Self, String>` - Returns `Ok(Self)` if the connection is successful,
-    ///   or an `Err(String)` containing an error message if the connection fails.
+    /// * `Result<Self, RfcError>` - Returns `Ok(Self)` if the connection is successful,
+    ///   or an `Err(RfcError)` carrying the SDK error information if the connection fails.
     /// # Errors
-    /// * Returns an error message if the connection fails, which can be caused by invalid parameters   
-    pub fn connect(mut self) -> Result<Self, String> {
-        // Initialize INI path if RFC_INI environment variable is set (only once)
-        let mut ini_initialized = INI_PATH_INITIALIZED.lock().map_err(any_to_string)?;
-        if !*ini_initialized {
-            if let Ok(rfc_ini_path) = std::env::var("RFC_INI") {
-                let ini_path = SapString::from(rfc_ini_path);
-                let mut err_info = error_info();
-                unsafe {
-                    RfcSetIniPath(ini_path.raw_pointer(), &mut err_info);
-                    if err_info.code != 0 {
-                        return Err(String::from(&SapString::from(err_info.message.as_slice())));
-                    }
-                }
-                trace!("RFC INI path set to: {:?}", ini_path);
+    /// * Returns `RfcError::Config` if the parameters are incomplete, see [`Connection::validate`].
+    /// * Returns `RfcError::Config` if the SDK is older than the minimum version of the
+    ///   applied `SdkConfig` and its policy is `VersionPolicy::Refuse`.
+    /// * Returns an error if the connection fails, which can be caused by invalid parameters,
+    ///   a failed logon (`RfcErrorGroup::LogonFailure`) or an unreachable system
+    ///   (`RfcErrorGroup::CommunicationFailure`).
+    pub fn connect(mut self) -> Result<Self, RfcError> {
+        self.validate()?;
+        self.open()?;
+        Ok(self)
+    }
+
+    /// Sends a ping to the SAP system to check that the connection is still usable.
+    /// # Returns
+    /// * `Result<(), RfcError>` - Returns `Ok(())` if the system answered, or an `Err(RfcError)`
+    ///   if the connection is closed or broken.
+    /// # Errors
+    /// * Returns an error with `RfcErrorGroup::CommunicationFailure` if the network connection
+    ///   was lost or the system was restarted, see [`Connection::reopen`].
+    pub fn ping(&self) -> Result<(), RfcError> {
+        let mut errorInfo = error_info();
+        let rc = unsafe { RfcPing(self.cn, &mut errorInfo) };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
+        }
+        Ok(())
+    }
+
+    /// Checks if the SDK still considers the connection handle valid.
+    /// Unlike [`Connection::ping`] this does not send anything to the SAP system, so a
+    /// connection that was dropped by the other side is only detected after the next call.
+    /// # Returns
+    /// * `bool` - `true` if the connection is open and its handle is valid, `false` otherwise.
+    pub fn is_alive(&self) -> bool {
+        if !self.is_connected() {
+            return false;
+        }
+        let mut errorInfo = error_info();
+        let mut isValid: cty::c_int = 0;
+        let rc = unsafe { RfcIsConnectionHandleValid(self.cn, &mut isValid, &mut errorInfo) };
+        rc == 0 && isValid != 0
+    }
+
+    /// Returns details about the open connection, like the system ID, client, user, language,
+    /// release and the partner host it landed on.
+    /// # Returns
+    /// * `Result<ConnectionAttributes, RfcError>` - Returns `Ok(ConnectionAttributes)` if the
+    ///   connection is open, or an `Err(RfcError)` with the SDK error information otherwise.
+    pub fn attributes(&self) -> Result<ConnectionAttributes, RfcError> {
+        let mut errorInfo = error_info();
+        let mut attr = rfc_attributes();
+        let rc = unsafe { RfcGetConnectionAttributes(self.cn, &mut attr, &mut errorInfo) };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
+        }
+        Ok(ConnectionAttributes::from(&attr))
+    }
+
+    /// Returns the SSO ticket issued for the logged on user, e.g. to log on to another
+    /// system on behalf of the user with [`Credentials::SsoTicket`].
+    /// The connection has to be opened with [`Connection::request_sso_ticket`], and the
+    /// profile parameter `login/create_sso2_ticket` must be set in the SAP system.
+    /// # Errors
+    /// * Returns the SDK error if no ticket is available.
+    pub fn partner_sso_ticket(&self) -> Result<Secret, RfcError> {
+        let mut ticket = vec![0u16; 4096];
+        loop {
+            let mut errorInfo = error_info();
+            let mut length = ticket.len() as cty::c_uint;
+            let rc = unsafe {
+                RfcGetPartnerSSOTicket(self.cn, ticket.as_mut_ptr(), &mut length, &mut errorInfo)
+            };
+            if rc == _RFC_RC_RFC_BUFFER_TOO_SMALL && length as usize > ticket.len() {
+                ticket = vec![0u16; length as usize];
+                continue;
+            }
+            if rc != 0 {
+                return Err(RfcError::from(errorInfo));
             }
-            *ini_initialized = true;
+            let secret = Secret::new(String::from_utf16_lossy(&ticket[..length as usize]));
+            ticket.zeroize();
+            return Ok(secret);
         }
-        drop(ini_initialized);
+    }
 
-        let mut x = CONNECT_COUNT.lock().map_err(any_to_string)?;
+    /// Starts a stateful session, see [`Session`].
+    /// The ABAP context is reset when the session is dropped.
+    pub fn session(&mut self) -> Session<'_> {
+        Session::new(self)
+    }
+
+    /// Resets the ABAP user context of the connection without closing it.
+    /// The state of all function groups is discarded, including changes that were not
+    /// committed.
+    /// # Errors
+    /// * Returns the SDK error if the context could not be reset. The connection should
+    ///   be reopened in that case.
+    pub fn reset_server_context(&self) -> Result<(), RfcError> {
+        let mut errorInfo = error_info();
+        let rc = unsafe { RfcResetServerContext(self.cn, &mut errorInfo) };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
+        }
+        Ok(())
+    }
+
+    /// Closes the current session, if any, and opens a new one with the stored parameters.
+    /// Use this after a communication failure, e.g. a network interruption or a restart
+    /// of the SAP system. Functions of the old session have to be dropped before and
+    /// retrieved again with [`Connection::function`] afterwards.
+    /// # Returns
+    /// * `Result<(), RfcError>` - Returns `Ok(())` if the new session was established.
+    /// # Errors
+    /// * Returns the same errors as [`Connection::connect`].
+    ///   The connection stays closed in this case.
+    pub fn reopen(&mut self) -> Result<(), RfcError> {
+        self.validate()?;
+        self.close();
+        self.open()
+    }
+
+    /// Opens the connection with the stored parameters and stores the handle.
+    fn open(&mut self) -> Result<(), RfcError> {
+        crate::sdk::initialize()?;
+
+        let mut x = CONNECT_COUNT.lock().map_err(any_to_error)?;
         let ps = self
             .params
             .iter()
@@ -94,71 +499,88 @@ impl Connection {
         trace!("parameter count: {}", ps.len());
         let cn = unsafe { RfcOpenConnection(ps.as_ptr(), ps.len() as u32, &mut err_info) };
         trace!("cn: {cn:p}");
-        trace!("par {:?}", ps[0]);
         // dump_memory(self.params[0].name.raw_pointer());
         // dump_memory(self.params[0].value.raw_pointer());
         if cn != 0 as *mut _RFC_CONNECTION_HANDLE {
             self.cn = cn;
+            if let Ok(mut shared) = self.shared.lock() {
+                shared.0 = cn;
+            }
             *x = *x + 1;
             trace!("open -> {} connections", *x);
         }
-        trace!(
-            "Key: {:}",
-            String::from(&SapString::from(err_info.key.as_slice()))
-        );
-        trace!(
-            "Message: {:}",
-            String::from(&SapString::from(err_info.message.as_slice()))
-        );
         if err_info.code != 0 {
-            return Err(String::from(&SapString::from(err_info.message.as_slice())));
+            return Err(RfcError::from(err_info));
         }
 
-        Ok(self)
+        Ok(())
+    }
+
+    /// Closes the connection if it is open and resets the handle.
+    pub(crate) fn close(&mut self) {
+        trace!("closing connection");
+        if self.is_connected() {
+            // the handle has to be closed even if another thread panicked with the count locked
+            let mut x = CONNECT_COUNT.lock().unwrap_or_else(|e| e.into_inner());
+            // no cancel may use the handle while and after it is closed
+            let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+            shared.0 = 0 as RFC_CONNECTION_HANDLE;
+            let mut errorInfo = error_info();
+            unsafe {
+                RfcCloseConnection(self.cn, &mut errorInfo);
+            }
+            drop(shared);
+            *x = *x - 1;
+            trace!("close -> {} connections", *x);
+            self.cn = 0 as RFC_CONNECTION_HANDLE;
+        }
     }
 
     /// Retrieves a function description for the specified function name.
     /// This function takes a string argument representing the function name,
     /// creates a `SapString` from it, and uses the SAP RFC API to get the function description.
     /// If the function description is successfully retrieved, it creates a new `Function` instance
-    /// and returns it. If there is an error, it returns an `Error` with the error message.
+    /// and returns it. If there is an error, it returns an `RfcError` with the SDK error information.
     /// # Arguments
     /// * `arg` - A string slice representing the name of the function to retrieve.
     /// # Returns
-    /// * `Result<Function, String>` - Returns `Ok(Function)` if the function is found,
-    ///   or an `Err(String)` containing an error message if the function is not found.
-    pub fn function(&self, arg: &str) -> Result<Function, String> {
+    /// * `Result<Function, RfcError>` - Returns `Ok(Function)` if the function is found,
+    ///   or an `Err(RfcError)` if the function is not found.
+    pub fn function(&self, arg: &str) -> Result<Function<'_>, RfcError> {
         let name = SapString::from(arg);
         let mut errorInfo = error_info();
         unsafe {
             let fd = RfcGetFunctionDesc(self.cn, name.raw_pointer(), &mut errorInfo);
-            if errorInfo.code != 0 {
-                return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+            if errorInfo.code != 0 || fd.is_null() {
+                return Err(RfcError::from(errorInfo));
             }
-            assert!(fd as usize != 0);
 
             let fh = RfcCreateFunction(fd, &mut errorInfo);
-            if errorInfo.code != 0 {
-                return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+            if errorInfo.code != 0 || fh.is_null() {
+                return Err(RfcError::from(errorInfo));
             }
-            assert_ne!(0, fh as usize);
-            Function::new(self.cn, fh, fd)
+            Function::new(self.cn, fh, fd).map(|f| f.with_cancel_handle(self.cancel_handle()))
         }
     }
+
+    /// Retrieves a function like [`Connection::function`] and enables class-based ABAP
+    /// exceptions for it.
+    /// If the function module raises a class-based exception, `Function::execute` returns
+    /// `RfcError::AbapClassException` with the exception class name and its attributes.
+    /// # Arguments
+    /// * `arg` - A string slice representing the name of the function to retrieve.
+    /// # Returns
+    /// * `Result<Function, RfcError>` - Returns `Ok(Function)` if the function is found
+    ///   and class exceptions could be enabled, or an `Err(RfcError)` otherwise.
+    pub fn function_with_class_exceptions(&self, arg: &str) -> Result<Function<'_>, RfcError> {
+        let f = self.function(arg)?;
+        f.enable_abap_class_exceptions()?;
+        Ok(f)
+    }
 }
 
 impl Drop for Connection {
     fn drop(&mut self) {
-        trace!("closing connection");
-        if self.is_connected() {
-            if let Ok(mut x) = CONNECT_COUNT.lock() {
-                let mut errorInfo = error_info();
-                unsafe {
-                    RfcCloseConnection(self.cn, &mut errorInfo);
-                }
-                *x = *x - 1;
-                trace!("close -> {} connections", *x);
-            }
-        }
+        self.close();
     }
 }
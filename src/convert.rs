@@ -0,0 +1,377 @@
+use chrono::{NaiveDate, NaiveTime};
+use log::trace;
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+
+use crate::{
+    abap_object, check,
+    error::RfcError,
+    get_string,
+    librfc::{
+        RfcGetAbapObject, RfcGetBytes, RfcGetChars, RfcGetDate, RfcGetFloat, RfcGetInt, RfcGetInt1,
+        RfcGetInt2, RfcGetInt8, RfcGetStringLength, RfcGetStructure, RfcGetTable, RfcGetTime,
+        RfcGetXString, RfcSetBytes, RfcSetChars, RfcSetDate, RfcSetFloat, RfcSetInt, RfcSetInt1,
+        RfcSetInt2, RfcSetInt8, RfcSetNum, RfcSetString, RfcSetStructure, RfcSetTable, RfcSetTime,
+        RfcSetXString, RfcType, RFC_ABAP_OBJECT_HANDLE, RFC_DATA_CONTAINER, RFC_STRUCTURE_HANDLE,
+        RFC_TABLE_HANDLE,
+    },
+    string::SapString,
+    structure::SapStructure,
+    table::SapTable,
+    value::{decode_bytes, format_utclong, parse_utclong, BytesEncoding, Value},
+};
+
+/// Reads a parameter of a function or a field of a structure into the `Value` that
+/// matches its ABAP type.
+/// Character-like types are read with their full length, numbers as `Int` or `Float`,
+/// RAW and XSTRING as `Bytes`. Packed numbers, decimal floating point numbers, time
+/// stamps and every other elementary type are read in their character representation,
+/// so no digits are lost.
+/// # Arguments
+/// * `cont` - A pointer to the RFC data container.
+/// * `name` - The name of the parameter or field.
+/// * `typ` - The type of the parameter or field.
+/// * `length` - The length in the non-Unicode layout, i.e. characters for CHAR and bytes for RAW.
+/// # Errors
+/// * Returns the SDK error if the value cannot be read.
+pub(crate) fn get_value(
+    cont: *mut RFC_DATA_CONTAINER,
+    name: &str,
+    typ: RfcType,
+    length: u32,
+) -> Result<Value, RfcError> {
+    trace!("getting {name} of type {typ:?}");
+    let sap_name = SapString::from(name);
+    let n = sap_name.raw_pointer();
+    let v = match typ {
+        RfcType::Char | RfcType::Num => {
+            let mut buffer = vec![0; length as usize];
+            check(|e| unsafe { RfcGetChars(cont, n, buffer.as_mut_ptr(), length, e) })?;
+            Value::String(SapString::from(buffer.as_slice()))
+        }
+        RfcType::Date => {
+            let mut buffer = [0; 8];
+            check(|e| unsafe { RfcGetDate(cont, n, buffer.as_mut_ptr(), e) })?;
+            let s = String::from(&SapString::from(buffer.as_slice()));
+            if s.trim().trim_matches('0').is_empty() {
+                Value::Empty
+            } else {
+                // ABAP does not check dates, invalid ones are passed on as they are
+                match NaiveDate::parse_from_str(&s, "%Y%m%d") {
+                    Ok(d) => Value::Date(d),
+                    Err(_) => Value::String(SapString::from(s)),
+                }
+            }
+        }
+        RfcType::Time => {
+            let mut buffer = [0; 6];
+            check(|e| unsafe { RfcGetTime(cont, n, buffer.as_mut_ptr(), e) })?;
+            let s = String::from(&SapString::from(buffer.as_slice()));
+            match NaiveTime::parse_from_str(&s, "%H%M%S") {
+                Ok(t) => Value::Time(t),
+                Err(_) => Value::String(SapString::from(s)),
+            }
+        }
+        RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34 => {
+            let s = String::from(&get_string(cont, &sap_name)?);
+            let s = s.trim();
+            // more than 28 significant digits do not fit into a `Decimal`
+            match Decimal::from_str_exact(s).or_else(|_| Decimal::from_scientific(s)) {
+                Ok(d) => Value::Decimal(d),
+                Err(_) => Value::String(SapString::from(s)),
+            }
+        }
+        RfcType::Utclong => {
+            let s = String::from(&get_string(cont, &sap_name)?);
+            let s = s.trim();
+            if s.is_empty() || s.starts_with("0000") {
+                Value::Empty
+            } else {
+                match parse_utclong(s) {
+                    Some(t) => Value::Timestamp(t),
+                    None => Value::String(SapString::from(s)),
+                }
+            }
+        }
+        RfcType::Int1 => {
+            let mut i = 0;
+            check(|e| unsafe { RfcGetInt1(cont, n, &mut i, e) })?;
+            Value::Int(i as i64)
+        }
+        RfcType::Int2 => {
+            let mut i = 0;
+            check(|e| unsafe { RfcGetInt2(cont, n, &mut i, e) })?;
+            Value::Int(i as i64)
+        }
+        RfcType::Int => {
+            let mut i = 0;
+            check(|e| unsafe { RfcGetInt(cont, n, &mut i, e) })?;
+            Value::Int(i as i64)
+        }
+        RfcType::Int8 => {
+            let mut i = 0;
+            check(|e| unsafe { RfcGetInt8(cont, n, &mut i, e) })?;
+            Value::Int(i)
+        }
+        RfcType::Float => {
+            let mut f = 0.0;
+            check(|e| unsafe { RfcGetFloat(cont, n, &mut f, e) })?;
+            Value::Float(f)
+        }
+        RfcType::Byte => {
+            let mut buffer = vec![0; length as usize];
+            check(|e| unsafe { RfcGetBytes(cont, n, buffer.as_mut_ptr(), length, e) })?;
+            Value::Bytes(buffer)
+        }
+        RfcType::XString => {
+            let mut len = 0;
+            check(|e| unsafe { RfcGetStringLength(cont, n, &mut len, e) })?;
+            let mut buffer = vec![0; len as usize];
+            let mut xstring_length = 0;
+            check(|e| unsafe {
+                RfcGetXString(cont, n, buffer.as_mut_ptr(), len, &mut xstring_length, e)
+            })?;
+            buffer.truncate(xstring_length as usize);
+            Value::Bytes(buffer)
+        }
+        RfcType::Structure | RfcType::Box | RfcType::GenericBox => {
+            let mut handle = 0 as RFC_STRUCTURE_HANDLE;
+            check(|e| unsafe { RfcGetStructure(cont, n, &mut handle, e) })?;
+            Value::Structure(SapStructure::new(handle, true)?)
+        }
+        RfcType::Table => {
+            let mut handle = 0 as RFC_TABLE_HANDLE;
+            check(|e| unsafe { RfcGetTable(cont, n, &mut handle, e) })?;
+            Value::Table(SapTable::new(handle, true))
+        }
+        RfcType::AbapObject => {
+            let mut handle = 0 as RFC_ABAP_OBJECT_HANDLE;
+            check(|e| unsafe { RfcGetAbapObject(cont, n, &mut handle, e) })?;
+            if handle.is_null() {
+                Value::Empty
+            } else {
+                Value::Object(abap_object::object_to_json(handle)?)
+            }
+        }
+        RfcType::Null => Value::Empty,
+        RfcType::String
+        | RfcType::XmlData
+        | RfcType::UtcSecond
+        | RfcType::UtcMinute
+        | RfcType::DtDay
+        | RfcType::DtWeek
+        | RfcType::DtMonth
+        | RfcType::TSecond
+        | RfcType::TMinute
+        | RfcType::CDay => Value::String(get_string(cont, &sap_name)?),
+    };
+    Ok(v)
+}
+
+/// Sets a parameter of a function or a field of a structure, converting the value into
+/// the ABAP type of the target.
+/// # Arguments
+/// * `cont` - A pointer to the RFC data container.
+/// * `name` - The name of the parameter or field.
+/// * `typ` - The type of the parameter or field.
+/// * `length` - The length in the non-Unicode layout, i.e. characters for NUMC and bytes for RAW.
+/// * `value` - The value to set.
+/// * `encoding` - The encoding of a string given for RAW or XSTRING.
+/// # Errors
+/// * Returns `RfcError::Conversion` if the value cannot be represented in the type of the
+///   target, e.g. an integer out of range or a malformed date, or the SDK error.
+pub(crate) fn set_value(
+    cont: *mut RFC_DATA_CONTAINER,
+    name: &str,
+    typ: RfcType,
+    length: u32,
+    value: Value,
+    encoding: BytesEncoding,
+) -> Result<(), RfcError> {
+    trace!("setting {name} of type {typ:?} from {}", value.kind());
+    let sap_name = SapString::from(name);
+    let n = sap_name.raw_pointer();
+    let error = |message: String| Err(RfcError::conversion(name, message));
+    match (typ, value) {
+        (RfcType::Char | RfcType::String, Value::String(s)) => {
+            check(|e| unsafe { RfcSetChars(cont, n, s.raw_pointer(), s.len() as u32, e) })
+        }
+        (
+            RfcType::Char | RfcType::String,
+            v @ (Value::Int(_) | Value::Float(_) | Value::Decimal(_) | Value::Bool(_)),
+        ) => {
+            let s = SapString::from(v.to_string());
+            check(|e| unsafe { RfcSetChars(cont, n, s.raw_pointer(), s.len() as u32, e) })
+        }
+        (RfcType::Int1 | RfcType::Int2 | RfcType::Int | RfcType::Int8, v) => {
+            let i = to_int(&v).ok_or_else(|| {
+                RfcError::conversion(name, format!("cannot convert {} to an integer", v.kind()))
+            })?;
+            let out_of_range = || error(format!("{i} is out of range for {typ:?}"));
+            match typ {
+                RfcType::Int1 => match u8::try_from(i) {
+                    Ok(i) => check(|e| unsafe { RfcSetInt1(cont, n, i, e) }),
+                    Err(_) => out_of_range(),
+                },
+                RfcType::Int2 => match i16::try_from(i) {
+                    Ok(i) => check(|e| unsafe { RfcSetInt2(cont, n, i, e) }),
+                    Err(_) => out_of_range(),
+                },
+                RfcType::Int => match i32::try_from(i) {
+                    Ok(i) => check(|e| unsafe { RfcSetInt(cont, n, i, e) }),
+                    Err(_) => out_of_range(),
+                },
+                _ => check(|e| unsafe { RfcSetInt8(cont, n, i as _, e) }),
+            }
+        }
+        (RfcType::Float, v) => {
+            let f = match &v {
+                Value::Float(f) => *f,
+                Value::Int(i) => *i as f64,
+                Value::Decimal(d) => match d.to_f64() {
+                    Some(f) => f,
+                    None => return error(format!("{d} is out of range for a float")),
+                },
+                Value::String(s) => match String::from(s).trim().parse() {
+                    Ok(f) => f,
+                    Err(_) => return error(format!("'{v}' is not a number")),
+                },
+                v => return error(format!("cannot convert {} to a float", v.kind())),
+            };
+            check(|e| unsafe { RfcSetFloat(cont, n, f, e) })
+        }
+        (RfcType::Bcd | RfcType::Decf16 | RfcType::Decf34, v) => {
+            // packed and decimal floating point numbers are passed in their exact
+            // character representation, the SDK rounds them to the decimals of the type
+            let s = match &v {
+                Value::Int(_) | Value::Float(_) | Value::Decimal(_) => v.to_string(),
+                Value::String(s) => String::from(s).trim().to_string(),
+                v => return error(format!("cannot convert {} to a decimal number", v.kind())),
+            };
+            if s.parse::<Decimal>().is_err() {
+                return error(format!("'{s}' is not a decimal number"));
+            }
+            let s = SapString::from(s);
+            check(|e| unsafe { RfcSetString(cont, n, s.raw_pointer(), s.len() as u32, e) })
+        }
+        (RfcType::Date, Value::String(s)) => {
+            let s = String::from(&s).replace('-', "");
+            let date = digits::<8>(&s).ok_or_else(|| {
+                RfcError::conversion(name, format!("'{s}' is not a date YYYYMMDD"))
+            })?;
+            check(|e| unsafe { RfcSetDate(cont, n, date.as_ptr(), e) })
+        }
+        (RfcType::Date, Value::Date(d)) => {
+            let date = digits::<8>(&d.format("%Y%m%d").to_string())
+                .ok_or_else(|| RfcError::conversion(name, format!("{d} is out of range")))?;
+            check(|e| unsafe { RfcSetDate(cont, n, date.as_ptr(), e) })
+        }
+        (RfcType::Time, Value::Time(t)) => {
+            let time = digits::<6>(&t.format("%H%M%S").to_string())
+                .ok_or_else(|| RfcError::conversion(name, format!("{t} is not a time HHMMSS")))?;
+            check(|e| unsafe { RfcSetTime(cont, n, time.as_ptr(), e) })
+        }
+        (RfcType::Utclong, Value::Timestamp(t)) => {
+            let s = SapString::from(format_utclong(&t));
+            check(|e| unsafe { RfcSetString(cont, n, s.raw_pointer(), s.len() as u32, e) })
+        }
+        (RfcType::Time, Value::String(s)) => {
+            let s = String::from(&s).replace(':', "");
+            let time = digits::<6>(&s)
+                .ok_or_else(|| RfcError::conversion(name, format!("'{s}' is not a time HHMMSS")))?;
+            check(|e| unsafe { RfcSetTime(cont, n, time.as_ptr(), e) })
+        }
+        (RfcType::Num, v @ (Value::String(_) | Value::Int(_))) => {
+            let s = v.to_string();
+            let s = s.trim();
+            if !s.chars().all(|c| c.is_ascii_digit()) || s.len() > length as usize {
+                return error(format!(
+                    "'{s}' is not a number with at most {length} digits"
+                ));
+            }
+            let s = SapString::from(format!("{s:0>width$}", width = length as usize));
+            check(|e| unsafe { RfcSetNum(cont, n, s.raw_pointer(), s.len() as u32, e) })
+        }
+        (RfcType::Byte, Value::Bytes(b)) => {
+            if b.len() > length as usize {
+                return error(format!("{} bytes do not fit into {length} bytes", b.len()));
+            }
+            check(|e| unsafe { RfcSetBytes(cont, n, b.as_ptr(), b.len() as u32, e) })
+        }
+        (RfcType::Byte | RfcType::XString, Value::String(s)) => {
+            let b = decode_bytes(name, &String::from(&s), encoding)?;
+            set_value(cont, name, typ, length, Value::Bytes(b), encoding)
+        }
+        (RfcType::XString, Value::Bytes(b)) => {
+            check(|e| unsafe { RfcSetXString(cont, n, b.as_ptr(), b.len() as u32, e) })
+        }
+        (
+            RfcType::XmlData
+            | RfcType::Utclong
+            | RfcType::UtcSecond
+            | RfcType::UtcMinute
+            | RfcType::DtDay
+            | RfcType::DtWeek
+            | RfcType::DtMonth
+            | RfcType::TSecond
+            | RfcType::TMinute
+            | RfcType::CDay,
+            Value::String(s),
+        ) => check(|e| unsafe { RfcSetString(cont, n, s.raw_pointer(), s.len() as u32, e) }),
+        (RfcType::Structure | RfcType::Box | RfcType::GenericBox, Value::Structure(s)) => {
+            check(|e| unsafe { RfcSetStructure(cont, n, s.handle(), e) })
+        }
+        (RfcType::Table, Value::Table(t)) => {
+            check(|e| unsafe { RfcSetTable(cont, n, t.handle(), e) })
+        }
+        (typ, v) => error(format!("cannot set {} for type {typ:?}", v.kind())),
+    }
+}
+
+/// Converts an elementary JSON value into a `Value` for [`set_value`].
+/// Integers become `Int`, other numbers `Decimal` so that no digits are lost, or `Float`
+/// if they are out of its range. Strings are converted by `set_value` according to the
+/// target type, e.g. dates, packed numbers or encoded bytes.
+/// # Errors
+/// * Returns `RfcError::Conversion` for arrays and objects.
+pub(crate) fn from_json(name: &str, value: &serde_json::Value) -> Result<Value, RfcError> {
+    match value {
+        serde_json::Value::Null => Ok(Value::Empty),
+        serde_json::Value::Bool(b) => Ok(Value::Bool(*b)),
+        serde_json::Value::Number(n) => Ok(match n.as_i64() {
+            Some(i) => Value::Int(i),
+            None => match Decimal::from_str_exact(&n.to_string()) {
+                Ok(d) => Value::Decimal(d),
+                Err(_) => Value::Float(n.as_f64().unwrap_or_default()),
+            },
+        }),
+        serde_json::Value::String(s) => Ok(Value::from(s.as_str())),
+        v => Err(RfcError::conversion(
+            name,
+            format!("expected an elementary value, got {v}"),
+        )),
+    }
+}
+
+/// Converts an integer, a number without fraction or a numeric string into an integer.
+fn to_int(value: &Value) -> Option<i64> {
+    match value {
+        Value::Int(i) => Some(*i),
+        Value::Decimal(d) if d.fract().is_zero() => d.to_i64(),
+        Value::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => Some(*f as i64),
+        Value::String(s) => String::from(s).trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Returns the characters of a string of exactly `N` decimal digits.
+fn digits<const N: usize>(s: &str) -> Option<[u16; N]> {
+    let s = s.trim();
+    if s.len() != N || !s.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut v = [0u16; N];
+    for (d, c) in v.iter_mut().zip(s.encode_utf16()) {
+        *d = c;
+    }
+    Some(v)
+}
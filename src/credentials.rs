@@ -0,0 +1,52 @@
+use crate::{rfc_param::RfcParam, secret::Secret};
+
+/// Names of the connection parameters that carry credentials or select the logon method.
+pub(crate) const CREDENTIAL_PARAMS: [&str; 6] = [
+    "user",
+    "passwd",
+    "mysapsso2",
+    "x509cert",
+    "snc_sso",
+    "tls_client_certificate_logon",
+];
+
+/// The way a client connection logs on to the SAP system.
+/// Setting credentials with [`crate::connection::Connection::credentials`] replaces the
+/// credentials set before, so the logon method can be changed without touching the code
+/// that uses the connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// Logon with user and password (`user`, `passwd`).
+    Password { user: String, passwd: Secret },
+    /// Logon with an SAP logon ticket or an assertion ticket (`mysapsso2`), in base64 form
+    /// as issued by the SAP system.
+    SsoTicket(Secret),
+    /// Logon with an X.509 certificate (`x509cert`) in base64 form without header and
+    /// footer. Requires SNC, see [`crate::connection::Connection::snc`].
+    X509(String),
+    /// Logon with the SNC identity of the process (`snc_sso`). Requires SNC.
+    Snc,
+}
+
+impl Credentials {
+    /// Creates password credentials.
+    pub fn password<S: Into<Secret>>(user: &str, passwd: S) -> Self {
+        Credentials::Password {
+            user: user.to_string(),
+            passwd: passwd.into(),
+        }
+    }
+
+    /// Returns the connection parameters for these credentials.
+    pub fn to_params(&self) -> Vec<RfcParam> {
+        match self {
+            Credentials::Password { user, passwd } => vec![
+                RfcParam::new("user", user.as_str()),
+                RfcParam::secret("passwd", passwd),
+            ],
+            Credentials::SsoTicket(ticket) => vec![RfcParam::secret("mysapsso2", ticket)],
+            Credentials::X509(cert) => vec![RfcParam::new("x509cert", cert.as_str())],
+            Credentials::Snc => vec![RfcParam::new("snc_sso", "1")],
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    librfc::{self, RFC_ERROR_GROUP, RFC_ERROR_INFO, RFC_RC},
+    string::SapString,
+};
+
+/// Return code of an SDK call, mirroring the `RFC_RC` enumeration of the SAP NetWeaver RFC SDK.
+/// Codes that are not known to this crate are kept as `Unknown` with their raw value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum RfcReturnCode {
+    Ok,
+    CommunicationFailure,
+    LogonFailure,
+    AbapRuntimeFailure,
+    AbapMessage,
+    AbapException,
+    Closed,
+    Canceled,
+    Timeout,
+    MemoryInsufficient,
+    VersionMismatch,
+    InvalidProtocol,
+    SerializationFailure,
+    InvalidHandle,
+    Retry,
+    ExternalFailure,
+    Executed,
+    NotFound,
+    NotSupported,
+    IllegalState,
+    InvalidParameter,
+    CodepageConversionFailure,
+    ConversionFailure,
+    BufferTooSmall,
+    TableMoveBof,
+    TableMoveEof,
+    StartSapguiFailure,
+    AbapClassException,
+    UnknownError,
+    AuthorizationFailure,
+    AuthenticationFailure,
+    CryptolibFailure,
+    IoFailure,
+    LockingFailure,
+    Unknown(i64),
+}
+
+impl From<RFC_RC> for RfcReturnCode {
+    fn from(value: RFC_RC) -> Self {
+        match value {
+            librfc::_RFC_RC_RFC_OK => Self::Ok,
+            librfc::_RFC_RC_RFC_COMMUNICATION_FAILURE => Self::CommunicationFailure,
+            librfc::_RFC_RC_RFC_LOGON_FAILURE => Self::LogonFailure,
+            librfc::_RFC_RC_RFC_ABAP_RUNTIME_FAILURE => Self::AbapRuntimeFailure,
+            librfc::_RFC_RC_RFC_ABAP_MESSAGE => Self::AbapMessage,
+            librfc::_RFC_RC_RFC_ABAP_EXCEPTION => Self::AbapException,
+            librfc::_RFC_RC_RFC_CLOSED => Self::Closed,
+            librfc::_RFC_RC_RFC_CANCELED => Self::Canceled,
+            librfc::_RFC_RC_RFC_TIMEOUT => Self::Timeout,
+            librfc::_RFC_RC_RFC_MEMORY_INSUFFICIENT => Self::MemoryInsufficient,
+            librfc::_RFC_RC_RFC_VERSION_MISMATCH => Self::VersionMismatch,
+            librfc::_RFC_RC_RFC_INVALID_PROTOCOL => Self::InvalidProtocol,
+            librfc::_RFC_RC_RFC_SERIALIZATION_FAILURE => Self::SerializationFailure,
+            librfc::_RFC_RC_RFC_INVALID_HANDLE => Self::InvalidHandle,
+            librfc::_RFC_RC_RFC_RETRY => Self::Retry,
+            librfc::_RFC_RC_RFC_EXTERNAL_FAILURE => Self::ExternalFailure,
+            librfc::_RFC_RC_RFC_EXECUTED => Self::Executed,
+            librfc::_RFC_RC_RFC_NOT_FOUND => Self::NotFound,
+            librfc::_RFC_RC_RFC_NOT_SUPPORTED => Self::NotSupported,
+            librfc::_RFC_RC_RFC_ILLEGAL_STATE => Self::IllegalState,
+            librfc::_RFC_RC_RFC_INVALID_PARAMETER => Self::InvalidParameter,
+            librfc::_RFC_RC_RFC_CODEPAGE_CONVERSION_FAILURE => Self::CodepageConversionFailure,
+            librfc::_RFC_RC_RFC_CONVERSION_FAILURE => Self::ConversionFailure,
+            librfc::_RFC_RC_RFC_BUFFER_TOO_SMALL => Self::BufferTooSmall,
+            librfc::_RFC_RC_RFC_TABLE_MOVE_BOF => Self::TableMoveBof,
+            librfc::_RFC_RC_RFC_TABLE_MOVE_EOF => Self::TableMoveEof,
+            librfc::_RFC_RC_RFC_START_SAPGUI_FAILURE => Self::StartSapguiFailure,
+            librfc::_RFC_RC_RFC_ABAP_CLASS_EXCEPTION => Self::AbapClassException,
+            librfc::_RFC_RC_RFC_UNKNOWN_ERROR => Self::UnknownError,
+            librfc::_RFC_RC_RFC_AUTHORIZATION_FAILURE => Self::AuthorizationFailure,
+            librfc::_RFC_RC_RFC_AUTHENTICATION_FAILURE => Self::AuthenticationFailure,
+            librfc::_RFC_RC_RFC_CRYPTOLIB_FAILURE => Self::CryptolibFailure,
+            librfc::_RFC_RC_RFC_IO_FAILURE => Self::IoFailure,
+            librfc::_RFC_RC_RFC_LOCKING_FAILURE => Self::LockingFailure,
+            x => Self::Unknown(x as i64),
+        }
+    }
+}
+
+/// Error group of an SDK error, mirroring the `RFC_ERROR_GROUP` enumeration.
+/// The group is the coarse classification the SDK assigns to every error and
+/// is the recommended way to decide how to react to a failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum RfcErrorGroup {
+    Ok,
+    AbapApplicationFailure,
+    AbapRuntimeFailure,
+    LogonFailure,
+    CommunicationFailure,
+    ExternalRuntimeFailure,
+    ExternalApplicationFailure,
+    ExternalAuthorizationFailure,
+    ExternalAuthenticationFailure,
+    CryptolibFailure,
+    LockingFailure,
+    Unknown(i64),
+}
+
+impl From<RFC_ERROR_GROUP> for RfcErrorGroup {
+    fn from(value: RFC_ERROR_GROUP) -> Self {
+        match value {
+            librfc::_RFC_ERROR_GROUP_OK => Self::Ok,
+            librfc::_RFC_ERROR_GROUP_ABAP_APPLICATION_FAILURE => Self::AbapApplicationFailure,
+            librfc::_RFC_ERROR_GROUP_ABAP_RUNTIME_FAILURE => Self::AbapRuntimeFailure,
+            librfc::_RFC_ERROR_GROUP_LOGON_FAILURE => Self::LogonFailure,
+            librfc::_RFC_ERROR_GROUP_COMMUNICATION_FAILURE => Self::CommunicationFailure,
+            librfc::_RFC_ERROR_GROUP_EXTERNAL_RUNTIME_FAILURE => Self::ExternalRuntimeFailure,
+            librfc::_RFC_ERROR_GROUP_EXTERNAL_APPLICATION_FAILURE => {
+                Self::ExternalApplicationFailure
+            }
+            librfc::_RFC_ERROR_GROUP_EXTERNAL_AUTHORIZATION_FAILURE => {
+                Self::ExternalAuthorizationFailure
+            }
+            librfc::_RFC_ERROR_GROUP_EXTERNAL_AUTHENTICATION_FAILURE => {
+                Self::ExternalAuthenticationFailure
+            }
+            librfc::_RFC_ERROR_GROUP_CRYPTOLIB_FAILURE => Self::CryptolibFailure,
+            librfc::_RFC_ERROR_GROUP_LOCKING_FAILURE => Self::LockingFailure,
+            x => Self::Unknown(x as i64),
+        }
+    }
+}
+
+/// Owned copy of an `RFC_ERROR_INFO` structure as filled in by the SDK.
+/// All character fields are converted to Rust strings with trailing blanks removed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RfcErrorInfo {
+    pub code: RfcReturnCode,
+    pub group: RfcErrorGroup,
+    pub key: String,
+    pub message: String,
+    pub abap_msg_class: String,
+    pub abap_msg_type: String,
+    pub abap_msg_number: String,
+    pub abap_msg_v1: String,
+    pub abap_msg_v2: String,
+    pub abap_msg_v3: String,
+    pub abap_msg_v4: String,
+}
+
+impl From<&RFC_ERROR_INFO> for RfcErrorInfo {
+    fn from(value: &RFC_ERROR_INFO) -> Self {
+        let s = |x: &[u16]| String::from(&SapString::from(x));
+        Self {
+            code: RfcReturnCode::from(value.code),
+            group: RfcErrorGroup::from(value.group),
+            key: s(value.key.as_slice()),
+            message: s(value.message.as_slice()),
+            abap_msg_class: s(value.abapMsgClass.as_slice()),
+            abap_msg_type: s(value.abapMsgType.as_slice()),
+            abap_msg_number: s(value.abapMsgNumber.as_slice()),
+            abap_msg_v1: s(value.abapMsgV1.as_slice()),
+            abap_msg_v2: s(value.abapMsgV2.as_slice()),
+            abap_msg_v3: s(value.abapMsgV3.as_slice()),
+            abap_msg_v4: s(value.abapMsgV4.as_slice()),
+        }
+    }
+}
+
+/// A classic ABAP exception raised by a remote function module (`RAISE` or `MESSAGE ... RAISING`).
+/// The `key` is the exception name as declared in the function interface, e.g. `NOT_FOUND`.
+/// If the exception was raised together with a message, the message class, number and
+/// variables are filled in as well.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AbapException {
+    pub key: String,
+    pub message: String,
+    pub msg_class: String,
+    pub msg_type: String,
+    pub msg_number: String,
+    pub vars: [String; 4],
+}
+
+impl AbapException {
+    /// Returns `true` if this is the exception with the given key.
+    pub fn is(&self, key: &str) -> bool {
+        self.key.eq_ignore_ascii_case(key)
+    }
+}
+
+impl From<RfcErrorInfo> for AbapException {
+    fn from(value: RfcErrorInfo) -> Self {
+        Self {
+            key: value.key,
+            message: value.message,
+            msg_class: value.abap_msg_class,
+            msg_type: value.abap_msg_type,
+            msg_number: value.abap_msg_number,
+            vars: [
+                value.abap_msg_v1,
+                value.abap_msg_v2,
+                value.abap_msg_v3,
+                value.abap_msg_v4,
+            ],
+        }
+    }
+}
+
+/// A class-based ABAP exception raised by a remote function module.
+/// The attributes of the exception object are converted into JSON values the same way
+/// any other `Value` is serialized, so they stay available after the function is dropped.
+#[derive(Debug, Clone, Serialize)]
+pub struct AbapClassException {
+    pub class_name: String,
+    pub message: String,
+    pub attributes: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Coarse classification of an error that tells how a caller should react to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ErrorClass {
+    /// The connection broke down or was closed. The session is lost, but the call
+    /// may succeed after reconnecting.
+    Communication,
+    /// The logon was rejected. Reconnecting with the same parameters will fail again.
+    Logon,
+    /// The ABAP side reported an error, e.g. an exception or a runtime error.
+    /// The connection is still usable.
+    Application,
+    /// Any other error, e.g. a conversion error or an invalid parameter.
+    Other,
+}
+
+/// Error type of all fallible operations of this crate.
+/// Errors reported by the SDK keep the complete error information, so callers can
+/// distinguish e.g. logon failures from communication failures or ABAP exceptions
+/// by looking at [`RfcError::code`] and [`RfcError::group`].
+#[derive(Debug, Clone)]
+pub enum RfcError {
+    /// An error reported by the SAP NetWeaver RFC SDK.
+    Sdk(RfcErrorInfo),
+    /// A classic exception raised by the called ABAP function module.
+    AbapException(AbapException),
+    /// A class-based exception raised by the called ABAP function module.
+    /// Only reported for functions created with class exceptions enabled.
+    AbapClassException(AbapClassException),
+    /// A BAPI reported an error (type `E` or `A`) in its `RETURN` parameter.
+    BapiReturn {
+        msg_type: String,
+        msg_class: String,
+        msg_number: String,
+        message: String,
+    },
+    /// A value of the named field or parameter could not be converted to or from its ABAP type.
+    Conversion { name: String, message: String },
+    /// The connection parameters are incomplete or contradictory.
+    Config(String),
+    /// An error detected by this crate before or after calling the SDK.
+    Internal(String),
+    /// The call was cancelled through a `CancelHandle`. The SDK closed the connection.
+    /// The SDK error information is kept; it is `None` if the call was cancelled before
+    /// it reached the SDK.
+    Cancelled(Option<RfcErrorInfo>),
+    /// The call did not finish within the given time and was cancelled.
+    TimedOut(std::time::Duration),
+}
+
+impl RfcError {
+    /// Creates a conversion error for the named field or parameter.
+    pub fn conversion<N, M>(name: N, message: M) -> Self
+    where
+        N: Into<String>,
+        M: Into<String>,
+    {
+        RfcError::Conversion {
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+
+    /// Returns the SDK error information, if the error was reported by the SDK.
+    pub fn info(&self) -> Option<&RfcErrorInfo> {
+        match self {
+            RfcError::Sdk(info) | RfcError::Cancelled(Some(info)) => Some(info),
+            _ => None,
+        }
+    }
+
+    /// Returns the SDK return code, if the error was reported by the SDK.
+    pub fn code(&self) -> Option<RfcReturnCode> {
+        match self {
+            RfcError::Sdk(info) => Some(info.code),
+            RfcError::AbapException(_) => Some(RfcReturnCode::AbapException),
+            RfcError::AbapClassException(_) => Some(RfcReturnCode::AbapClassException),
+            RfcError::Cancelled(_) => Some(RfcReturnCode::Canceled),
+            RfcError::TimedOut(_) => Some(RfcReturnCode::Timeout),
+            _ => None,
+        }
+    }
+
+    /// Returns the SDK error group, if the error was reported by the SDK.
+    pub fn group(&self) -> Option<RfcErrorGroup> {
+        match self {
+            RfcError::Sdk(info) | RfcError::Cancelled(Some(info)) => Some(info.group),
+            RfcError::AbapException(_)
+            | RfcError::AbapClassException(_)
+            | RfcError::BapiReturn { .. } => Some(RfcErrorGroup::AbapApplicationFailure),
+            _ => None,
+        }
+    }
+
+    /// Returns the ABAP exception, if the error is a classic ABAP exception.
+    pub fn abap_exception(&self) -> Option<&AbapException> {
+        match self {
+            RfcError::AbapException(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns the class-based ABAP exception, if the error is one.
+    pub fn abap_class_exception(&self) -> Option<&AbapClassException> {
+        match self {
+            RfcError::AbapClassException(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the error is the ABAP exception with the given key.
+    pub fn is_abap_exception(&self, key: &str) -> bool {
+        self.abap_exception().is_some_and(|e| e.is(key))
+    }
+
+    /// Returns `true` if the logon to the SAP system failed.
+    pub fn is_logon_failure(&self) -> bool {
+        self.group() == Some(RfcErrorGroup::LogonFailure)
+    }
+
+    /// Classifies the error, see [`ErrorClass`].
+    /// Cancelled calls are never classified as communication failures, so they are not
+    /// repeated by a `ResilientConnection`.
+    pub fn class(&self) -> ErrorClass {
+        if let RfcError::Cancelled(_) | RfcError::TimedOut(_) = self {
+            return ErrorClass::Other;
+        }
+        match (self.code(), self.group()) {
+            (Some(RfcReturnCode::Closed | RfcReturnCode::InvalidHandle), _)
+            | (_, Some(RfcErrorGroup::CommunicationFailure)) => ErrorClass::Communication,
+            (_, Some(RfcErrorGroup::LogonFailure)) => ErrorClass::Logon,
+            (
+                _,
+                Some(RfcErrorGroup::AbapApplicationFailure | RfcErrorGroup::AbapRuntimeFailure),
+            ) => ErrorClass::Application,
+            _ => ErrorClass::Other,
+        }
+    }
+
+    /// Returns `true` if the connection to the SAP system broke down or was closed,
+    /// i.e. the error is of class [`ErrorClass::Communication`].
+    pub fn is_communication_failure(&self) -> bool {
+        self.class() == ErrorClass::Communication
+    }
+}
+
+impl From<RFC_ERROR_INFO> for RfcError {
+    fn from(value: RFC_ERROR_INFO) -> Self {
+        let info = RfcErrorInfo::from(&value);
+        match info.code {
+            RfcReturnCode::AbapException => RfcError::AbapException(AbapException::from(info)),
+            RfcReturnCode::Canceled => RfcError::Cancelled(Some(info)),
+            _ => RfcError::Sdk(info),
+        }
+    }
+}
+
+impl Display for RfcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RfcError::Sdk(info) => write!(f, "{} ({:?}): {}", info.key, info.code, info.message),
+            RfcError::AbapException(e) => write!(f, "ABAP exception {}: {}", e.key, e.message),
+            RfcError::AbapClassException(e) => {
+                write!(f, "ABAP class exception {}: {}", e.class_name, e.message)
+            }
+            RfcError::BapiReturn {
+                msg_type,
+                msg_class,
+                msg_number,
+                message,
+            } => write!(
+                f,
+                "BAPI error {msg_type} {msg_class}/{msg_number}: {message}"
+            ),
+            RfcError::Conversion { name, message } => write!(f, "{name}: {message}"),
+            RfcError::Config(message) => write!(f, "invalid connection parameters: {message}"),
+            RfcError::Internal(message) => write!(f, "{message}"),
+            RfcError::Cancelled(Some(info)) => write!(f, "call was cancelled: {}", info.message),
+            RfcError::Cancelled(None) => write!(f, "call was cancelled"),
+            RfcError::TimedOut(timeout) => write!(f, "call timed out after {timeout:?}"),
+        }
+    }
+}
+
+impl std::error::Error for RfcError {}
@@ -1,19 +1,27 @@
-use core::error;
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{Condvar, Mutex},
+    time::{Duration, Instant},
+};
 
-use log::{error, info, trace};
+use lazy_static::lazy_static;
+use log::{error, info, trace, warn};
 use serde::{
     ser::{self, SerializeMap},
     Serialize,
 };
 
 use crate::{
-    error_info,
+    abap_object, check,
+    connection::{CancelHandle, Connection},
+    convert::{from_json, get_value, set_value},
+    error::{AbapException, RfcError, RfcReturnCode},
+    error_info, exception_description,
     librfc::{
-        RfcDestroyFunction, RfcDestroyFunctionDesc, RfcGetChars, RfcGetInt, RfcGetParameterCount,
-        RfcGetParameterDescByIndex, RfcGetParameterDescByName, RfcGetString, RfcGetStringLength,
-        RfcGetStructure, RfcGetTable, RfcGetXString, RfcInvoke, RfcType, RFC_CONNECTION_HANDLE,
-        RFC_DATA_CONTAINER, RFC_FUNCTION_DESC_HANDLE, RFC_FUNCTION_HANDLE, RFC_INT,
-        RFC_STRUCTURE_HANDLE, RFC_TABLE_HANDLE, RFC_TYPE_DESC_HANDLE,
+        RfcDestroyFunction, RfcEnableAbapClassException, RfcGetExceptionCount,
+        RfcGetExceptionDescByIndex, RfcGetParameterCount, RfcGetParameterDescByIndex,
+        RfcGetParameterDescByName, RfcInvoke, RfcType,
         _RFCTYPE_RFCTYPE_CHAR as RFCTYPE_RFCTYPE_CHAR, _RFCTYPE_RFCTYPE_INT as RFCTYPE_RFCTYPE_INT,
         _RFCTYPE_RFCTYPE_STRING as RFCTYPE_RFCTYPE_STRING,
         _RFCTYPE_RFCTYPE_STRUCTURE as RFCTYPE_RFCTYPE_STRUCTURE,
@@ -21,14 +29,13 @@ use crate::{
         _RFC_DIRECTION_RFC_CHANGING as RFC_DIRECTION_RFC_CHANGING,
         _RFC_DIRECTION_RFC_EXPORT as RFC_DIRECTION_RFC_EXPORT,
         _RFC_DIRECTION_RFC_IMPORT as RFC_DIRECTION_RFC_IMPORT,
-        _RFC_DIRECTION_RFC_TABLES as RFC_DIRECTION_RFC_TABLES,
+        _RFC_DIRECTION_RFC_TABLES as RFC_DIRECTION_RFC_TABLES, RFC_CONNECTION_HANDLE,
+        RFC_DATA_CONTAINER, RFC_FUNCTION_DESC_HANDLE, RFC_FUNCTION_HANDLE, RFC_PARAMETER_DESC,
+        RFC_TYPE_DESC_HANDLE,
     },
-    parameter_description, set_chars, set_chars_from_str, set_structure_from_type_handle,
-    set_table_from_type_handle, set_xstring_from_str,
+    parameter_description, set_structure_from_type_handle, set_table_from_type_handle,
     string::SapString,
-    structure::SapStructure,
-    table,
-    value::Value,
+    value::{BytesEncoding, SerializeWith, Value},
 };
 
 #[derive(Debug, Clone, Copy, Serialize)]
@@ -49,21 +56,44 @@ pub struct ParameterDescription {
     pub type_desc_handle: RFC_TYPE_DESC_HANDLE,
 }
 
-pub struct Function {
+/// A classic exception declared in the interface of a function module.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExceptionDescription {
+    pub key: String,
+    pub message: String,
+}
+
+/// A function module of the SAP system, created by [`Connection::function`].
+/// It borrows the connection it was created from, so the connection can neither be closed
+/// nor moved to another thread while the function is in use.
+pub struct Function<'c> {
     cn: RFC_CONNECTION_HANDLE,
     fh: RFC_FUNCTION_HANDLE,
     fd: RFC_FUNCTION_DESC_HANDLE,
     params: Vec<ParameterDescription>,
+    exceptions: Vec<ExceptionDescription>,
+    cancel: Option<CancelHandle>,
+    connection: PhantomData<&'c Connection>,
 }
 
-impl Serialize for Function {
+impl Serialize for Function<'_> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.serialize_with(serializer, BytesEncoding::default())
+    }
+}
+
+impl SerializeWith for Function<'_> {
+    fn serialize_with<S>(&self, serializer: S, encoding: BytesEncoding) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         let mut map = serializer.serialize_map(None)?;
         for p in self.params.iter() {
-            map.serialize_entry(&p.name, &self.get(&p.name).map_err(ser::Error::custom)?)?
+            let value = self.get(&p.name).map_err(ser::Error::custom)?;
+            map.serialize_entry(&p.name, &value.with_bytes_encoding(encoding))?
             // match p.direction {
             //     ParameterDirection::Import => {
             //         map.serialize_entry(&p.name, &self.get(&p.name).map_err(ser::Error::custom)?)?
@@ -83,43 +113,54 @@ impl Serialize for Function {
     }
 }
 
-impl Function {
+impl Function<'_> {
     pub fn new(
         cn: RFC_CONNECTION_HANDLE,
         fh: RFC_FUNCTION_HANDLE,
         fd: RFC_FUNCTION_DESC_HANDLE,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, RfcError> {
+        // owns the function handle from here on, so it is destroyed on every error path
+        let mut function = Self {
+            cn,
+            fh,
+            fd,
+            params: vec![],
+            exceptions: vec![],
+            cancel: None,
+            connection: PhantomData,
+        };
         let mut errorInfo = error_info();
-        let mut params = vec![];
         let mut count: cty::c_uint = 0;
         let rc = unsafe { RfcGetParameterCount(fd, &mut count as *mut u32, &mut errorInfo) };
-        if errorInfo.code != 0 {
-            return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+        if errorInfo.code != 0 || rc != 0 {
+            return Err(RfcError::from(errorInfo));
         }
-        assert_eq!(rc, 0);
         trace!("param count: {count}");
         for i in 0..count {
             let mut paramDesc = parameter_description();
             let rc = unsafe { RfcGetParameterDescByIndex(fd, i, &mut paramDesc, &mut errorInfo) };
-            if errorInfo.code != 0 {
-                return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+            if errorInfo.code != 0 || rc != 0 {
+                return Err(RfcError::from(errorInfo));
             }
 
-            assert_eq!(rc, 0);
-
             let s = paramDesc.name.as_slice();
+            let name = String::from(&SapString::from(s));
             let direction = match paramDesc.direction as i32 {
                 RFC_DIRECTION_RFC_IMPORT => ParameterDirection::Import,
                 RFC_DIRECTION_RFC_EXPORT => ParameterDirection::Export,
                 RFC_DIRECTION_RFC_CHANGING => ParameterDirection::Changing,
                 RFC_DIRECTION_RFC_TABLES => ParameterDirection::Table,
-                _ => panic!("Unknown parameter direction: {}", paramDesc.direction),
+                x => {
+                    return Err(RfcError::conversion(
+                        name,
+                        format!("unknown parameter direction {x}"),
+                    ))
+                }
             };
             let length = paramDesc.ucLength as i32;
             let decimals = paramDesc.decimals as i32;
             let datatype = paramDesc.type_;
-            let name = String::from(&SapString::from(s));
-            params.push(ParameterDescription {
+            function.params.push(ParameterDescription {
                 name,
                 datatype,
                 direction,
@@ -129,246 +170,325 @@ impl Function {
             })
         }
 
-        Ok(Self { cn, fh, fd, params })
+        let mut count: cty::c_uint = 0;
+        check(|e| unsafe { RfcGetExceptionCount(fd, &mut count, e) })?;
+        trace!("exception count: {count}");
+        for i in 0..count {
+            let mut excDesc = exception_description();
+            check(|e| unsafe { RfcGetExceptionDescByIndex(fd, i, &mut excDesc, e) })?;
+            function.exceptions.push(ExceptionDescription {
+                key: String::from(&SapString::from(excDesc.key.as_slice())),
+                message: String::from(&SapString::from(excDesc.message.as_slice())),
+            });
+        }
+
+        Ok(function)
+    }
+
+    /// Attaches the cancel handle of the connection the function was created from.
+    pub(crate) fn with_cancel_handle(mut self, cancel: CancelHandle) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Returns the classic exceptions declared in the interface of the function module.
+    pub fn exceptions(&self) -> &[ExceptionDescription] {
+        &self.exceptions
+    }
+
+    /// Checks if the function module declares an exception with the given key.
+    pub fn has_exception(&self, key: &str) -> bool {
+        self.exceptions
+            .iter()
+            .any(|e| e.key.eq_ignore_ascii_case(key))
+    }
+
+    /// Enables class-based ABAP exceptions for this function.
+    /// Without this, the SDK reports class-based exceptions raised by the function module
+    /// as runtime failures. With it, `execute` returns `RfcError::AbapClassException`
+    /// carrying the exception class and its attributes.
+    pub fn enable_abap_class_exceptions(&self) -> Result<(), RfcError> {
+        let mut errorInfo = error_info();
+        let rc = unsafe { RfcEnableAbapClassException(self.fh, self.cn, &mut errorInfo) };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
+        }
+        Ok(())
     }
 
-    pub fn execute(&self) -> Result<(), String> {
+    pub fn execute(&self) -> Result<(), RfcError> {
         trace!("Executing function");
         let mut errorInfo = error_info();
         unsafe {
             let rc = RfcInvoke(self.cn, self.fh as *mut RFC_DATA_CONTAINER, &mut errorInfo);
             if errorInfo.code != 0 {
-                error!(
-                    "{} {} {} {} {}", errorInfo.code,
-                    String::from(&SapString::from(errorInfo.abapMsgClass.as_slice())),
-                    String::from(&SapString::from(errorInfo.abapMsgType.as_slice())),
-                    String::from(&SapString::from(errorInfo.abapMsgNumber.as_slice())),
-                    String::from(&SapString::from(errorInfo.message.as_slice()))
-                );
-                return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+                let e = match RfcError::from(errorInfo) {
+                    RfcError::Sdk(info) if info.code == RfcReturnCode::AbapClassException => {
+                        abap_object::class_exception(self.fh, info.clone())
+                            .map(RfcError::AbapClassException)
+                            .unwrap_or_else(|err| {
+                                error!("failed to read class exception: {err}");
+                                RfcError::Sdk(info)
+                            })
+                    }
+                    e => e,
+                };
+                if let Some(info) = e.info() {
+                    error!(
+                        "{:?} {} {} {} {}",
+                        info.code,
+                        info.abap_msg_class,
+                        info.abap_msg_type,
+                        info.abap_msg_number,
+                        info.message
+                    );
+                }
+                return Err(e);
+            }
+            if rc != 0 {
+                return Err(RfcError::from(errorInfo));
             }
-            assert_eq!(0, rc);
         }
         trace!("Executing function done");
         Ok(())
     }
 
-    pub fn set<V>(&self, name: &str, value: V) -> Result<(), String>
+    /// Executes the function like [`Function::execute`], but cancels the call if it does not
+    /// finish within `timeout`. Cancelling closes the connection, so it has to be reopened
+    /// with `Connection::reopen` before the next call.
+    /// The calls of all functions are watched by one watchdog thread.
+    /// # Errors
+    /// * Returns `RfcError::TimedOut` if the call failed because it was cancelled, or any
+    ///   error of [`Function::execute`]. A call that finishes just when the timeout expires
+    ///   keeps its own result, but the connection may be closed by the cancel anyway; use
+    ///   `Connection::is_alive` to check it.
+    pub fn execute_with_timeout(&self, timeout: Duration) -> Result<(), RfcError> {
+        let cancel = self.cancel.clone().ok_or_else(|| {
+            RfcError::Internal("function was not created from a connection".to_string())
+        })?;
+        let watchdog = WATCHDOG.clone().map_err(RfcError::Internal)?;
+        let call = watchdog.watch(timeout, cancel);
+        let result = self.execute();
+        let cancelled = !watchdog.unwatch(call);
+        match result {
+            Err(e)
+                if cancelled
+                    && (matches!(e, RfcError::Cancelled(_)) || e.is_communication_failure()) =>
+            {
+                Err(RfcError::TimedOut(timeout))
+            }
+            result => result,
+        }
+    }
+
+    /// Executes the function like [`Function::execute`], but treats exceptions declared in the
+    /// function interface as regular outcomes.
+    /// If the function module raises one of its declared exceptions, e.g. `NOT_FOUND`, the
+    /// exception is returned as `Ok(Some(exception))`. All other errors, including exceptions
+    /// that are not part of the interface, are returned as `Err`.
+    pub fn try_execute(&self) -> Result<Option<AbapException>, RfcError> {
+        match self.execute() {
+            Ok(()) => Ok(None),
+            Err(RfcError::AbapException(e)) if self.has_exception(&e.key) => Ok(Some(e)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Sets a parameter of the function.
+    /// The value is converted into the ABAP type of the parameter, e.g. an integer can be
+    /// set for INT1 to INT8, FLOAT, packed numbers, NUMC or CHAR parameters, a date is
+    /// given as `YYYYMMDD` string and RAW or XSTRING data as bytes or as base64 string.
+    /// # Errors
+    /// * Returns `RfcError::Conversion` if the value cannot be represented in the type of
+    ///   the parameter, or the SDK error if the parameter does not exist.
+    pub fn set<V>(&self, name: &str, value: V) -> Result<(), RfcError>
     where
         V: Into<Value>,
     {
-        let v: Value = value.into();
-        match v {
-            Value::String(sap_string) => set_chars(self.fh, name, sap_string)?,
-            _ => todo!(),
-        }
-        Ok(())
+        let paramDesc = self.describe(name)?;
+        let typ = RfcType::try_from(paramDesc.type_)?;
+        set_value(
+            self.fh,
+            name,
+            typ,
+            paramDesc.nucLength,
+            value.into(),
+            BytesEncoding::default(),
+        )
     }
 
-    pub fn get(&self, name: &str) -> Result<Value, String> {
-        trace!("Getting value for parameter: {}", name);
+    /// Returns the description of the parameter with the given name.
+    fn describe(&self, name: &str) -> Result<RFC_PARAMETER_DESC, RfcError> {
         let mut paramDesc = parameter_description();
         let mut errorInfo = error_info();
         let name = SapString::from(name);
-        unsafe {
-            RfcGetParameterDescByName(self.fd, name.raw_pointer(), &mut paramDesc, &mut errorInfo);
-        }
-        if errorInfo.code != 0 {
-            return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+        let rc = unsafe {
+            RfcGetParameterDescByName(self.fd, name.raw_pointer(), &mut paramDesc, &mut errorInfo)
+        };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
         }
-        let v = match RfcType::from(paramDesc.type_) {
-            RfcType::Int => {
-                let mut value: RFC_INT = 0;
-                unsafe { RfcGetInt(self.fh, name.raw_pointer(), &mut value, &mut errorInfo) };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                Value::Int(value as i64)
-            }
-            RfcType::Structure => {
-                let mut structHandle: RFC_STRUCTURE_HANDLE = 0 as RFC_STRUCTURE_HANDLE;
-                let rc = unsafe {
-                    RfcGetStructure(
-                        self.fh,
-                        name.raw_pointer(),
-                        &mut structHandle,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                trace!("structure handle: {:p}", structHandle);
-                Value::Structure(SapStructure::new(structHandle, true).unwrap())
-            }
-            RfcType::Table => {
-                let mut handle: RFC_TABLE_HANDLE = 0 as RFC_TABLE_HANDLE;
-                let rc = unsafe {
-                    RfcGetTable(self.fh, name.raw_pointer(), &mut handle, &mut errorInfo)
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                trace!("table handle: {:p}", handle);
-                Value::Table(table::SapTable::new(handle, true))
-            }
-            RfcType::String => {
-                trace!("getting string value for {:?}", name);
-                let mut strlen = 0u32;
-                let mut buf0 = [0; 5];
-                let _rc = unsafe {
-                    RfcGetString(
-                        self.fh,
-                        name.raw_pointer(),
-                        buf0.as_mut_ptr(),
-                        buf0.len() as u32,
-                        &mut strlen,
-                        &mut errorInfo,
-                    )
-                };
-                let mut buffer = vec![0; strlen as usize + 1];
-
-                let rc = unsafe {
-                    RfcGetString(
-                        self.fh,
-                        name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        buffer.len() as u32,
-                        std::ptr::null_mut(),
-                        &mut errorInfo,
-                    )
-                };
+        Ok(paramDesc)
+    }
 
-                if errorInfo.code != 0 {
-                    let s = String::from(&SapString::from(errorInfo.message.as_slice()));
-                    error!("RfcGetString failed: {}", &s);
-                    return Err(s);
-                }
-                assert_eq!(0, rc);
-                Value::String(SapString::from(buffer.as_slice()))
-            }
-            RfcType::Char => {
-                trace!("getting char value for {:?}", name);
-                let mut buffer = vec![0; paramDesc.ucLength as usize + 1];
-                let rc = unsafe {
-                    RfcGetChars(
-                        self.fh,
-                        name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        paramDesc.ucLength as u32,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                Value::String(SapString::from(buffer.as_slice()))
-            }
-            RfcType::XString => {
-                trace!("getting xstring value for {:?}", name);
-                let mut n: u32 = 0;
-                let rc = unsafe {
-                    RfcGetStringLength(self.fh, name.raw_pointer(), &mut n, &mut errorInfo)
-                };
-                let mut buffer = vec![0; n as usize];
-                let mut xstring_length: u32 = 0;
-                let rc = unsafe {
-                    RfcGetXString(
-                        self.fh,
-                        name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        buffer.len() as u32,
-                        &mut xstring_length,
-                        &mut errorInfo,
-                    )
-                };
+    /// Returns the value of a parameter of the function.
+    /// Every ABAP type is converted, see `Value` for the mapping.
+    pub fn get(&self, name: &str) -> Result<Value, RfcError> {
+        trace!("Getting value for parameter: {}", name);
+        let paramDesc = self.describe(name)?;
+        let typ = RfcType::try_from(paramDesc.type_)?;
+        get_value(self.fh, name, typ, paramDesc.nucLength)
+    }
 
-                if errorInfo.code != 0 {
-                    let s = String::from(&SapString::from(errorInfo.message.as_slice()));
-                    error!("RfcGetXString failed: {}", &s);
-                    return Err(s);
-                }
-                assert_eq!(0, rc);
-                let s = unsafe {
-                    String::from_utf8_unchecked(buffer[0..xstring_length as usize].to_vec())
-                };
-                Value::String(SapString::from(s.as_str()))
-            }
-            _ => todo!(
-                "Unsupported parameter type: {} for parameter {:?}",
-                paramDesc.type_,
-                name
-            ),
-        };
-        trace!("get value done");
-        Ok(v)
+    /// Sets the parameters given as JSON object, with RAW and XSTRING data as base64 strings.
+    /// See [`Function::set_parameters_with`].
+    pub fn set_parameters(&self, p: &serde_json::Value) -> Result<(), RfcError> {
+        self.set_parameters_with(p, BytesEncoding::default())
     }
 
-    pub fn set_parameters(&self, p: &serde_json::Value) -> Result<(), String> {
+    /// Sets the parameters given as JSON object, structures as nested objects and tables
+    /// as arrays of objects. Null values are skipped.
+    /// # Arguments
+    /// * `p` - A JSON object with the parameter names as keys.
+    /// * `encoding` - The encoding of strings given for RAW and XSTRING parameters and fields.
+    /// # Errors
+    /// * Returns `RfcError::Conversion` if a value cannot be converted into the type of its
+    ///   parameter, or the SDK error if a parameter does not exist.
+    pub fn set_parameters_with(
+        &self,
+        p: &serde_json::Value,
+        encoding: BytesEncoding,
+    ) -> Result<(), RfcError> {
         info!("settings parameters");
         match p {
             serde_json::Value::Object(map) => {
                 for (key, value) in map.iter() {
                     info!("Setting parameter: {} = {:?}", key, value);
                     let name = key.as_str();
-                    let sap_name = SapString::from(name);
-                    let mut paramDesc = parameter_description();
-                    let mut errorInfo = error_info();
-                    unsafe {
-                        RfcGetParameterDescByName(
-                            self.fd,
-                            sap_name.raw_pointer(),
-                            &mut paramDesc,
-                            &mut errorInfo,
-                        )
-                    };
-                    let typ = RfcType::from(paramDesc.type_);
+                    let paramDesc = self.describe(name)?;
+                    let typ = RfcType::try_from(paramDesc.type_)?;
                     match (value, typ) {
-                        (serde_json::Value::String(s), RfcType::String) => {
-                            set_chars_from_str(self.fh, name, s.as_str())?
-                        }
-                        (serde_json::Value::String(s), RfcType::XString) => {
-                            set_xstring_from_str(self.fh, name, s.as_str())?
-                        }
-                        (serde_json::Value::String(s), RfcType::Char) => {
-                            set_chars_from_str(self.fh, name, s.as_str())?
-                        }
+                        (serde_json::Value::Null, _) => {}
                         (serde_json::Value::Object(o), RfcType::Structure) => {
                             set_structure_from_type_handle(
                                 self.fh,
                                 name,
                                 paramDesc.typeDescHandle,
                                 o,
+                                encoding,
                             )?
                         }
                         (serde_json::Value::Array(a), RfcType::Table) => {
-                            set_table_from_type_handle(self.fh, name, paramDesc.typeDescHandle, a)?
-                        }
-                        (value, typ) => {
-                            return Err(format!(
-                                "Unsupported parameter type combination: {:?}, {:?}",
-                                value, typ
-                            ))
+                            set_table_from_type_handle(
+                                self.fh,
+                                name,
+                                paramDesc.typeDescHandle,
+                                a,
+                                encoding,
+                            )?
                         }
+                        (value, typ) => set_value(
+                            self.fh,
+                            name,
+                            typ,
+                            paramDesc.nucLength,
+                            from_json(name, value)?,
+                            encoding,
+                        )?,
                     }
                 }
             }
-            _ => return Err(format!("Unsupported parameter type: {}", p)),
+            _ => {
+                return Err(RfcError::Internal(format!(
+                    "Unsupported parameter type: {}",
+                    p
+                )))
+            }
         }
         trace!("set parameters done");
         Ok(())
     }
 }
-impl Drop for Function {
+lazy_static! {
+    static ref WATCHDOG: Result<&'static Watchdog, String> = Watchdog::start();
+}
+
+/// Cancels the calls of [`Function::execute_with_timeout`] that did not finish in time.
+#[derive(Default)]
+struct Watchdog {
+    calls: Mutex<WatchedCalls>,
+    changed: Condvar,
+}
+
+#[derive(Default)]
+struct WatchedCalls {
+    deadlines: BTreeMap<(Instant, u64), CancelHandle>,
+    next_id: u64,
+}
+
+impl Watchdog {
+    /// Starts the watchdog thread, which runs as long as the process.
+    fn start() -> Result<&'static Watchdog, String> {
+        let watchdog: &'static Watchdog = Box::leak(Box::default());
+        std::thread::Builder::new()
+            .name("sap-rfc-watchdog".to_string())
+            .spawn(move || watchdog.run())
+            .map_err(|e| e.to_string())?;
+        Ok(watchdog)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, WatchedCalls> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Watches a call that is cancelled with `cancel` after `timeout`.
+    fn watch(&self, timeout: Duration, cancel: CancelHandle) -> (Instant, u64) {
+        let mut calls = self.lock();
+        let call = (Instant::now() + timeout, calls.next_id);
+        calls.next_id += 1;
+        calls.deadlines.insert(call, cancel);
+        self.changed.notify_one();
+        call
+    }
+
+    /// Stops watching a call. Returns `false` if the call was cancelled already.
+    fn unwatch(&self, call: (Instant, u64)) -> bool {
+        self.lock().deadlines.remove(&call).is_some()
+    }
+
+    fn run(&self) {
+        let mut calls = self.lock();
+        loop {
+            let now = Instant::now();
+            calls = match calls.deadlines.first_key_value().map(|(call, _)| *call) {
+                None => self.changed.wait(calls).unwrap_or_else(|e| e.into_inner()),
+                Some(call) if call.0 <= now => {
+                    // cancelled under the lock, so a call that was unwatched is never cancelled
+                    if let Some(cancel) = calls.deadlines.remove(&call) {
+                        warn!("cancelling call after its timeout");
+                        if let Err(e) = cancel.cancel() {
+                            warn!("cancel failed: {e}");
+                        }
+                    }
+                    calls
+                }
+                Some(call) => {
+                    self.changed
+                        .wait_timeout(calls, call.0 - now)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+            };
+        }
+    }
+}
+
+impl Drop for Function<'_> {
     fn drop(&mut self) {
-        let mut errorInfo1 = error_info();
-        let mut errorInfo2 = error_info();
+        let mut errorInfo = error_info();
         unsafe {
-            RfcDestroyFunction(self.fh, &mut errorInfo2); // destry funtion handle first
-                                                          // RfcDestroyFunctionDesc(self.fd, &mut errorInfo1);
+            // the function description is cached by the SDK and must not be destroyed
+            RfcDestroyFunction(self.fh, &mut errorInfo);
         }
         trace!("drop function done");
     }
@@ -0,0 +1,217 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::{
+    connection::Connection,
+    error::RfcError,
+    rfc_param::{is_secret_name, RfcParam},
+    secret::MASK,
+};
+
+/// Name of the file the SDK looks for in the directory given by `RfcSetIniPath`.
+pub const INI_FILE_NAME: &str = "sapnwrfc.ini";
+
+/// One `DEST=` entry of a `sapnwrfc.ini` file.
+/// Parameter names are stored in upper case, the way the SDK documents them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Destination {
+    pub name: String,
+    pub params: BTreeMap<String, String>,
+}
+
+impl Destination {
+    /// Returns the value of the parameter with the given name, ignoring case.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .get(&name.to_ascii_uppercase())
+            .map(|v| v.as_str())
+    }
+
+    /// Returns a copy of the destination with the values of all password parameters
+    /// replaced, so it can be printed or logged.
+    pub fn masked(&self) -> Self {
+        let mut d = self.clone();
+        for (name, value) in d.params.iter_mut() {
+            if is_secret_name(name) {
+                *value = MASK.to_string();
+            }
+        }
+        d
+    }
+
+    /// Checks that the destination contains a complete set of logon information,
+    /// using the same rules as [`Connection::validate`].
+    /// # Errors
+    /// * Returns `RfcError::Config` naming the destination and the first missing or
+    ///   conflicting parameter.
+    pub fn validate(&self) -> Result<(), RfcError> {
+        self.params
+            .iter()
+            .fold(Connection::new(), |c, (name, value)| {
+                c.param(RfcParam::new(name.to_ascii_lowercase(), value.as_str()))
+            })
+            .validate()
+            .map_err(|e| match e {
+                RfcError::Config(msg) => {
+                    RfcError::Config(format!("destination '{}': {msg}", self.name))
+                }
+                e => e,
+            })
+    }
+}
+
+/// The parsed content of a `sapnwrfc.ini` file.
+/// Parameters before the first `DEST=` line, or after a `DEFAULT` line, are global
+/// settings like `RFC_TRACE` and are kept in `defaults`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct IniFile {
+    pub defaults: BTreeMap<String, String>,
+    pub destinations: Vec<Destination>,
+}
+
+impl IniFile {
+    /// Returns the path of the `sapnwrfc.ini` file the SDK uses: the file in the directory
+    /// given by the `RFC_INI` environment variable, or the one in the current directory.
+    /// Returns `None` if the file does not exist.
+    pub fn locate() -> Option<PathBuf> {
+        let dir = std::env::var_os("RFC_INI")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        let path = dir.join(INI_FILE_NAME);
+        path.is_file().then_some(path)
+    }
+
+    /// Reads and parses a `sapnwrfc.ini` file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, RfcError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| RfcError::Config(format!("{}: {e}", path.display())))?;
+        Self::parse(&content).map_err(|e| match e {
+            RfcError::Config(msg) => RfcError::Config(format!("{}: {msg}", path.display())),
+            e => e,
+        })
+    }
+
+    /// Parses the content of a `sapnwrfc.ini` file.
+    /// Every line holds one `NAME=VALUE` pair, lines starting with `#` are comments.
+    /// A `DEST=` line starts a new destination.
+    /// # Errors
+    /// * Returns `RfcError::Config` with the line number for a line that is not a
+    ///   parameter, or for a destination that is defined twice.
+    pub fn parse(s: &str) -> Result<Self, RfcError> {
+        let mut ini = IniFile::default();
+        let mut current: Option<Destination> = None;
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.eq_ignore_ascii_case("DEFAULT") {
+                if let Some(d) = current.take() {
+                    ini.push(d)?;
+                }
+                continue;
+            }
+            let (name, value) = line.split_once('=').ok_or_else(|| {
+                RfcError::Config(format!(
+                    "line {}: expected NAME=VALUE, got '{line}'",
+                    idx + 1
+                ))
+            })?;
+            let name = name.trim().to_ascii_uppercase();
+            let value = value.trim().to_string();
+            if name == "DEST" {
+                if let Some(d) = current.take() {
+                    ini.push(d)?;
+                }
+                current = Some(Destination {
+                    name: value,
+                    params: BTreeMap::new(),
+                });
+                continue;
+            }
+            match current.as_mut() {
+                Some(d) => d.params.insert(name, value),
+                None => ini.defaults.insert(name, value),
+            };
+        }
+        if let Some(d) = current {
+            ini.push(d)?;
+        }
+        Ok(ini)
+    }
+
+    fn push(&mut self, d: Destination) -> Result<(), RfcError> {
+        if self.get(&d.name).is_some() {
+            return Err(RfcError::Config(format!(
+                "destination '{}' is defined more than once",
+                d.name
+            )));
+        }
+        self.destinations.push(d);
+        Ok(())
+    }
+
+    /// Returns the names of all destinations in the order of the file.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.destinations.iter().map(|d| d.name.as_str())
+    }
+
+    /// Returns the destination with the given name. Like the SDK, the name is not case-sensitive.
+    pub fn get(&self, name: &str) -> Option<&Destination> {
+        self.destinations
+            .iter()
+            .find(|d| d.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the name of the destination that is most similar to `name`,
+    /// to be used in a "did you mean" hint for a misspelled destination.
+    /// Returns `None` if no destination is reasonably close.
+    pub fn suggest(&self, name: &str) -> Option<&str> {
+        let name = name.to_ascii_uppercase();
+        let max_distance = (name.chars().count() / 3).max(2);
+        self.names()
+            .map(|n| (edit_distance(&name, &n.to_ascii_uppercase()), n))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, n)| n)
+    }
+
+    /// Returns the destination with the given name.
+    /// # Errors
+    /// * Returns `RfcError::Config` if there is no such destination, including a
+    ///   suggestion for a similar destination name if there is one.
+    pub fn destination(&self, name: &str) -> Result<&Destination, RfcError> {
+        self.get(name).ok_or_else(|| {
+            let hint = match self.suggest(name) {
+                Some(s) => format!(", did you mean '{s}'?"),
+                None => String::new(),
+            };
+            RfcError::Config(format!("unknown destination '{name}'{hint}"))
+        })
+    }
+}
+
+/// Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
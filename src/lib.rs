@@ -9,12 +9,14 @@ use string::SapString;
 
 
 pub use connection::{Connection};
+pub use error::RfcError;
 pub use value::Value;
-pub use function::Function;
+pub use function::{ExceptionDescription, Function};
+pub use sdk::{sdk_version, SdkVersion};
 
 
 use crate::{librfc::{
-    _RFC_FIELD_DESC, _RFC_TYPE_DESC_HANDLE, _RFCTYPE_RFCTYPE_CHAR, _RFCTYPE_RFCTYPE_STRING, _RFCTYPE_RFCTYPE_STRUCTURE, _RFCTYPE_RFCTYPE_TABLE, RFC_DATA_CONTAINER, RFC_ERROR_INFO, RFC_FIELD_DESC, RFC_PARAMETER_DESC, RFC_TYPE_DESC_HANDLE, RfcAppendNewRow, RfcCreateStructure, RfcCreateTable, RfcGetFieldCount, RfcGetFieldDescByIndex, RfcSetChars, RfcSetStructure, RfcSetTable, RfcSetXString
+    _RFC_FIELD_DESC, _RFC_TYPE_DESC_HANDLE, _RFCTYPE_RFCTYPE_CHAR, RFC_DATA_CONTAINER, RFC_ERROR_INFO, RFC_RC, RFC_EXCEPTION_DESC, RFC_FIELD_DESC, RFC_PARAMETER_DESC, RFC_TYPE_DESC_HANDLE, RfcAppendNewRow, RfcCreateStructure, RfcCreateTable, RfcGetFieldCount, RfcGetFieldDescByIndex, RfcGetString, RfcSetStructure, RfcSetTable, RfcSetXString, RfcType
 }};
 
 lazy_static! {
@@ -22,8 +24,8 @@ lazy_static! {
     static ref INI_PATH_INITIALIZED: Mutex<bool> = Mutex::new(false);
 }
 
-fn any_to_string<T: Display>(value: T) -> String {
-    value.to_string()
+fn any_to_error<T: Display>(value: T) -> RfcError {
+    RfcError::Internal(value.to_string())
 }
 
 
@@ -32,7 +34,7 @@ fn any_to_string<T: Display>(value: T) -> String {
 #[allow(dead_code)]
 mod librfc {
     #[repr(i32)]
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum RfcType {
         Char = _RFCTYPE_RFCTYPE_CHAR,
         Date = _RFCTYPE_RFCTYPE_DATE,
@@ -49,11 +51,62 @@ mod librfc {
         Structure = _RFCTYPE_RFCTYPE_STRUCTURE,
         Table = _RFCTYPE_RFCTYPE_TABLE,
         XString = _RFCTYPE_RFCTYPE_XSTRING,   
+        Decf16 = _RFCTYPE_RFCTYPE_DECF16,
+        Decf34 = _RFCTYPE_RFCTYPE_DECF34,
+        Null = _RFCTYPE_RFCTYPE_NULL,
+        AbapObject = _RFCTYPE_RFCTYPE_ABAPOBJECT,
+        XmlData = _RFCTYPE_RFCTYPE_XMLDATA,
+        Utclong = _RFCTYPE_RFCTYPE_UTCLONG,
+        UtcSecond = _RFCTYPE_RFCTYPE_UTCSECOND,
+        UtcMinute = _RFCTYPE_RFCTYPE_UTCMINUTE,
+        DtDay = _RFCTYPE_RFCTYPE_DTDAY,
+        DtWeek = _RFCTYPE_RFCTYPE_DTWEEK,
+        DtMonth = _RFCTYPE_RFCTYPE_DTMONTH,
+        TSecond = _RFCTYPE_RFCTYPE_TSECOND,
+        TMinute = _RFCTYPE_RFCTYPE_TMINUTE,
+        CDay = _RFCTYPE_RFCTYPE_CDAY,
+        Box = _RFCTYPE_RFCTYPE_BOX,
+        GenericBox = _RFCTYPE_RFCTYPE_GENERIC_BOX,
     }
 
-    impl From<i32> for RfcType {
-        fn from(value: i32) -> Self {
-            unsafe { std::mem::transmute(value) }
+    impl TryFrom<RFCTYPE> for RfcType {
+        type Error = crate::RfcError;
+
+        fn try_from(value: RFCTYPE) -> Result<Self, Self::Error> {
+            match value {
+                _RFCTYPE_RFCTYPE_CHAR => Ok(RfcType::Char),
+                _RFCTYPE_RFCTYPE_DATE => Ok(RfcType::Date),
+                _RFCTYPE_RFCTYPE_TIME => Ok(RfcType::Time),
+                _RFCTYPE_RFCTYPE_BYTE => Ok(RfcType::Byte),
+                _RFCTYPE_RFCTYPE_FLOAT => Ok(RfcType::Float),
+                _RFCTYPE_RFCTYPE_INT1 => Ok(RfcType::Int1),
+                _RFCTYPE_RFCTYPE_INT2 => Ok(RfcType::Int2),
+                _RFCTYPE_RFCTYPE_INT8 => Ok(RfcType::Int8),
+                _RFCTYPE_RFCTYPE_BCD => Ok(RfcType::Bcd),
+                _RFCTYPE_RFCTYPE_NUM => Ok(RfcType::Num),
+                _RFCTYPE_RFCTYPE_INT => Ok(RfcType::Int),
+                _RFCTYPE_RFCTYPE_STRING => Ok(RfcType::String),
+                _RFCTYPE_RFCTYPE_STRUCTURE => Ok(RfcType::Structure),
+                _RFCTYPE_RFCTYPE_TABLE => Ok(RfcType::Table),
+                _RFCTYPE_RFCTYPE_XSTRING => Ok(RfcType::XString),
+                _RFCTYPE_RFCTYPE_DECF16 => Ok(RfcType::Decf16),
+                _RFCTYPE_RFCTYPE_DECF34 => Ok(RfcType::Decf34),
+                _RFCTYPE_RFCTYPE_NULL => Ok(RfcType::Null),
+                _RFCTYPE_RFCTYPE_ABAPOBJECT => Ok(RfcType::AbapObject),
+                _RFCTYPE_RFCTYPE_XMLDATA => Ok(RfcType::XmlData),
+                _RFCTYPE_RFCTYPE_UTCLONG => Ok(RfcType::Utclong),
+                _RFCTYPE_RFCTYPE_UTCSECOND => Ok(RfcType::UtcSecond),
+                _RFCTYPE_RFCTYPE_UTCMINUTE => Ok(RfcType::UtcMinute),
+                _RFCTYPE_RFCTYPE_DTDAY => Ok(RfcType::DtDay),
+                _RFCTYPE_RFCTYPE_DTWEEK => Ok(RfcType::DtWeek),
+                _RFCTYPE_RFCTYPE_DTMONTH => Ok(RfcType::DtMonth),
+                _RFCTYPE_RFCTYPE_TSECOND => Ok(RfcType::TSecond),
+                _RFCTYPE_RFCTYPE_TMINUTE => Ok(RfcType::TMinute),
+                _RFCTYPE_RFCTYPE_CDAY => Ok(RfcType::CDay),
+                _RFCTYPE_RFCTYPE_BOX => Ok(RfcType::Box),
+                _RFCTYPE_RFCTYPE_GENERIC_BOX => Ok(RfcType::GenericBox),
+                x => Err(crate::RfcError::Internal(format!("Unsupported RFC type: {x}"))),
+            }
         }
     }
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
@@ -61,7 +114,25 @@ mod librfc {
 pub mod rfc_param;
 mod string;
 
+mod abap_object;
+mod convert;
+#[cfg(feature = "async")]
+pub mod async_connection;
+pub mod attributes;
+
+pub mod config;
 pub mod connection;
+pub mod credentials;
+pub mod error;
+pub mod ini;
+pub mod pool;
+pub mod resilient;
+pub mod sdk;
+pub mod secret;
+pub mod session;
+pub mod shared;
+pub mod snc;
+pub mod websocket;
 
 mod function;
 
@@ -111,6 +182,15 @@ fn parameter_description() -> RFC_PARAMETER_DESC {
     paramDesc
 }
 
+/// Creates a zero-initialized `RFC_EXCEPTION_DESC` structure.
+/// The structure holds the key and the message text of an exception declared in a function interface.
+fn exception_description() -> RFC_EXCEPTION_DESC {
+    RFC_EXCEPTION_DESC {
+        key: zero(),
+        message: zero(),
+    }
+}
+
 /// Creates a zero-initialized array of `u16` with the specified size.
 /// This function returns an array of `u16` with all elements set to zero.
 fn zero<const N: usize>() -> [u16; N] {
@@ -140,6 +220,20 @@ fn error_info() -> RFC_ERROR_INFO {
     z
 }
 
+/// Calls an SDK function that reports failures through an `RFC_ERROR_INFO` and
+/// turns a return code other than `RFC_OK` into an error.
+fn check<F>(f: F) -> Result<(), RfcError>
+where
+    F: FnOnce(*mut RFC_ERROR_INFO) -> RFC_RC,
+{
+    let mut errorInfo = error_info();
+    let rc = f(&mut errorInfo);
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    Ok(())
+}
+
 /// Dumps the memory content of a pointer to a 16-bit character array.
 /// This function takes a pointer to a 16-bit character array and prints the first 16 characters
 /// in hexadecimal format for debugging purposes.
@@ -153,62 +247,44 @@ fn dump_memory(name_ptr: *const u16) {
     }
 }
 
-/// Sets a character field in the RFC data container from a `SapString`.
-/// This function takes a pointer to the RFC data container, a field name, and a `SapString` value.
-/// It uses the `RfcSetChars` function to set the value in the RFC data container.
-/// If the operation fails, it returns an error message.
+/// Sets a xstring field in the RFC data container from a base64 encoded string.
+/// The string is decoded and the resulting bytes are set with the `RfcSetXString` function.
 /// # Arguments
 /// * `cont` - A pointer to the RFC data container.
 /// * `name` - The name of the field to set.
-/// * `value` - The `SapString` value to set in the field.
+/// * `value` - The base64 encoded bytes.
 /// # Returns
-/// * `Result<(), String>` - Returns Ok(()) on success, or an error message on failure.
+/// * `Result<(), RfcError>` - Returns Ok(()) on success, or the SDK error on failure.
 /// # Errors
-/// * Returns an error if the `RfcSetChars` function fails, containing the error message from the SAP system.       
-///
-fn set_chars(cont: *mut RFC_DATA_CONTAINER, name: &str, value: SapString) -> Result<(), String> {
-    let mut errorInfo = error_info();
-    let str_name = SapString::from(name);
-
-    unsafe {
-        let rc = RfcSetChars(
-            cont,
-            str_name.raw_pointer(),
-            value.raw_pointer(),
-            value.len() as u32,
-            &mut errorInfo,
-        );
-        trace!("set value for {}: {:?} -> {}", name, value, rc);
-        if rc != 0 {
-            let x = SapString::from(errorInfo.message.as_slice());
-            return Err(String::from(&x));
-        }
-        assert_eq!(0, rc);
-    }
-    Ok(())
+/// * Returns `RfcError::Conversion` if the string is not base64 encoded.
+/// * Returns an error if the `RfcSetXString` function fails, carrying the error information from the SAP system.
+pub fn set_xstring_from_str(
+    cont: *mut crate::RFC_DATA_CONTAINER,
+    name: &str,
+    value: &str,
+) -> Result<(), RfcError> {
+    set_xstring_from_str_with_encoding(cont, name, value, value::BytesEncoding::Base64)
 }
 
-
-/// Sets a xstring field in the RFC data container from a string.
-/// This function takes a pointer to the RFC data container, a field name, and a string value.
-/// It converts the string to a `SapString` and uses the `RfcSetChars` function to set the value.
-/// If the operation fails, it returns an error message.
+/// Sets a xstring field in the RFC data container from an encoded string, like
+/// [`set_xstring_from_str`] but with the given encoding.
 /// # Arguments
-/// * `cont` - A pointer to the RFC data container.     
+/// * `cont` - A pointer to the RFC data container.
 /// * `name` - The name of the field to set.
-/// * `value` - The string value to set in the field.
-/// # Returns
-/// * `Result<(), String>` - Returns Ok(()) on success, or an error message on failure.
+/// * `value` - The base64 or hexadecimal encoded bytes.
+/// * `encoding` - The encoding of `value`.
 /// # Errors
-/// * Returns an error if the `RfcSetChars` function fails, containing the error message from the SAP system.
-pub fn set_xstring_from_str(
+/// * Returns `RfcError::Conversion` if the string is not encoded with the given encoding.
+/// * Returns an error if the `RfcSetXString` function fails, carrying the error information from the SAP system.
+pub fn set_xstring_from_str_with_encoding(
     cont: *mut crate::RFC_DATA_CONTAINER,
     name: &str,
     value: &str,
-) -> Result<(), String> {
+    encoding: value::BytesEncoding,
+) -> Result<(), RfcError> {
     let mut errorInfo = error_info();
     let str_name = SapString::from(name);
-    let value = value.as_bytes();
+    let value = value::decode_bytes(name, value, encoding)?;
     unsafe {
         let rc = RfcSetXString(
             cont,
@@ -219,50 +295,50 @@ pub fn set_xstring_from_str(
         );
         trace!("set value for {}: {:?} -> {}", name, value, rc);
         if rc != 0 {
-            let x = SapString::from(errorInfo.message.as_slice());
-            return Err(String::from(&x));
+            return Err(RfcError::from(errorInfo));
         }
-        assert_eq!(0, rc);
     }
     Ok(())
 }
 
-/// Sets a character field in the RFC data container from a string.
-/// This function takes a pointer to the RFC data container, a field name, and a string value.
-/// It converts the string to a `SapString` and uses the `RfcSetChars` function to set the value.
-/// If the operation fails, it returns an error message.
+/// Reads a field of the RFC data container in its character representation.
+/// The SDK converts every elementary type into a string, so this function can be used
+/// for fields whose exact type does not matter to the caller.
+/// The buffer is sized by a first call that only determines the length of the value.
 /// # Arguments
-/// * `cont` - A pointer to the RFC data container.     
-/// * `name` - The name of the field to set.
-/// * `value` - The string value to set in the field.
+/// * `cont` - A pointer to the RFC data container.
+/// * `name` - The name of the field to read.
 /// # Returns
-/// * `Result<(), String>` - Returns Ok(()) on success, or an error message on failure.
-/// # Errors
-/// * Returns an error if the `RfcSetChars` function fails, containing the error message from the SAP system.
-fn set_chars_from_str(
-    cont: *mut crate::RFC_DATA_CONTAINER,
-    name: &str,
-    value: &str,
-) -> Result<(), String> {
+/// * `Result<SapString, RfcError>` - The value of the field, or the SDK error on failure.
+fn get_string(cont: *mut RFC_DATA_CONTAINER, name: &SapString) -> Result<SapString, RfcError> {
     let mut errorInfo = error_info();
-    let str_name = SapString::from(name);
-    let value = SapString::from(value);
-    unsafe {
-        let rc = RfcSetChars(
+    let mut strlen = 0u32;
+    let mut buf0 = [0; 5];
+    let _rc = unsafe {
+        RfcGetString(
             cont,
-            str_name.raw_pointer(),
-            value.raw_pointer(),
-            value.len() as u32,
+            name.raw_pointer(),
+            buf0.as_mut_ptr(),
+            buf0.len() as u32,
+            &mut strlen,
             &mut errorInfo,
-        );
-        trace!("set value for {}: {:?} -> {}", name, value, rc);
-        if rc != 0 {
-            let x = SapString::from(errorInfo.message.as_slice());
-            return Err(String::from(&x));
-        }
-        assert_eq!(0, rc);
+        )
+    };
+    let mut buffer = vec![0; strlen as usize + 1];
+    let rc = unsafe {
+        RfcGetString(
+            cont,
+            name.raw_pointer(),
+            buffer.as_mut_ptr(),
+            buffer.len() as u32,
+            std::ptr::null_mut(),
+            &mut errorInfo,
+        )
+    };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
     }
-    Ok(())
+    Ok(SapString::from(buffer.as_slice()))
 }
 
 /// Sets a table in the RFC data container from a JSON array.
@@ -275,8 +351,9 @@ fn set_chars_from_str(
 /// * `name` - The name of the table to set.
 /// * `type_handle` - The type handle of the table.
 /// * `values` - A JSON array containing the values to set in the table.
+/// * `encoding` - The encoding of strings given for RAW and XSTRING fields.
 /// # Returns
-/// * `Result<(), String>` - Returns Ok(()) on success, or an error message on failure.
+/// * `Result<(), RfcError>` - Returns Ok(()) on success, or the SDK error on failure.
 /// # Errors
 /// * Returns an error if the field type is unsupported or if setting the table fails.
 fn set_structure_from_type_handle(
@@ -284,10 +361,14 @@ fn set_structure_from_type_handle(
     name: &str,
     type_handle: crate::RFC_TYPE_DESC_HANDLE,
     value: &serde_json::Map<String, serde_json::Value>,
-) -> Result<(), String> {
+    encoding: value::BytesEncoding,
+) -> Result<(), RfcError> {
     let mut errorInfo = error_info();
     let structure_handle = unsafe { RfcCreateStructure(type_handle, &mut errorInfo) };
-    fill_structure(structure_handle, type_handle, value)?;
+    if structure_handle.is_null() {
+        return Err(RfcError::from(errorInfo));
+    }
+    fill_structure(structure_handle, type_handle, value, encoding)?;
 
     let name_sap = SapString::from(name);
     let rc = unsafe {
@@ -299,11 +380,8 @@ fn set_structure_from_type_handle(
         )
     };
     if rc != 0 {
-        let x = SapString::from(errorInfo.message.as_slice());
-        trace!("sap string created");
-        return Err(String::from(&x));
+        return Err(RfcError::from(errorInfo));
     }
-    assert_eq!(0, rc);
 
     Ok(())
 }
@@ -311,83 +389,75 @@ fn set_structure_from_type_handle(
 /// Fills an RFC data container structure from a JSON map.
 /// This function iterates over the fields in the structure type handle and sets the values in the
 /// RFC data container according to the field type.
-/// It supports fields of type CHAR, STRING, STRUCTURE, and TABLE.
+/// Elementary fields are converted into their ABAP type like in `Function::set`, null values are skipped.
 /// If a field is of type STRUCTURE, it recursively calls itself to fill the structure from the type handle.
 /// If a field is of type TABLE, it calls `set_table_from_type_handle` to set the table values.
 /// # Arguments
 /// * `row_handle` - A pointer to the RFC data container.
 /// * `row_type_handle` - A pointer to the type handle of the structure.            
 /// * `values_map` - A map containing the field names and their corresponding JSON values.
+/// * `encoding` - The encoding of strings given for RAW and XSTRING fields.
 /// # Returns
-/// * `Result<(), String>` - Returns Ok(()) on success, or an error message on failure.
+/// * `Result<(), RfcError>` - Returns Ok(()) on success, or the SDK error on failure.
 /// # Errors
 /// * Returns an error if the field type is unsupported or if setting the structure fails.  
 /// # Note
 /// * This function assumes that the `row_handle` and `row_type_handle` are valid pointers to an RFC data container and its type description, respectively.
 /// * It logs the processing of each field index and the expected type for each field.
-/// * It handles different JSON value types (string, object, array) according to the field type.
-/// * It uses `set_chars_from_str`, `set_structure_from_type_handle`, and `set_table_from_type_handle` to set the values in the RFC data container.
-/// * It logs warnings if the expected type does not match the provided JSON value type.
+/// * It uses `convert::set_value`, `set_structure_from_type_handle`, and `set_table_from_type_handle` to set the values in the RFC data container.
 fn fill_structure(
     row_handle: *mut RFC_DATA_CONTAINER,
     row_type_handle: *mut _RFC_TYPE_DESC_HANDLE,
     values_map: &serde_json::Map<String, serde_json::Value>,
-) -> Result<(), String> {
+    encoding: value::BytesEncoding,
+) -> Result<(), RfcError> {
     let mut errorInfo = error_info();
     let mut count = 0;
-    unsafe {
-        RfcGetFieldCount(row_type_handle, &mut count, &mut errorInfo);
+    let rc = unsafe { RfcGetFieldCount(row_type_handle, &mut count, &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
     }
 
     Ok(for idx in 0..count {
         info!("Processing field index: {}", idx);
         let mut fieldDescr = field_descriptor();
-        unsafe {
-            RfcGetFieldDescByIndex(row_type_handle, idx, &mut fieldDescr, &mut errorInfo);
+        let rc = unsafe {
+            RfcGetFieldDescByIndex(row_type_handle, idx, &mut fieldDescr, &mut errorInfo)
+        };
+        if rc != 0 {
+            return Err(RfcError::from(errorInfo));
         }
         let name = SapString::new(&fieldDescr.name);
         let name = String::from(&name);
         if let Some(v) = values_map.get(&name) {
-            match fieldDescr.type_ {
-                _RFCTYPE_RFCTYPE_CHAR => {
-                    if let serde_json::Value::String(v) = v {
-                        set_chars_from_str(row_handle, name.as_str(), v.as_str())?;
-                    } else {
-                        info!("Expected string for field: {}, got {:?}", name, v);
-                    }
+            match (RfcType::try_from(fieldDescr.type_)?, v) {
+                (_, serde_json::Value::Null) => {}
+                (RfcType::Structure, serde_json::Value::Object(obj)) => {
+                    set_structure_from_type_handle(
+                        row_handle,
+                        name.as_str(),
+                        fieldDescr.typeDescHandle,
+                        obj,
+                        encoding,
+                    )?;
                 }
-                _RFCTYPE_RFCTYPE_STRUCTURE => {
-                    if let serde_json::Value::Object(obj) = v {
-                        set_structure_from_type_handle(
-                            row_handle,
-                            name.as_str(),
-                            fieldDescr.typeDescHandle,
-                            &obj.clone(),
-                        )?;
-                    } else {
-                        info!("Expected structure for field: {}, got {:?}", name, v);
-                    }
+                (RfcType::Table, serde_json::Value::Array(arr)) => {
+                    set_table_from_type_handle(
+                        row_handle,
+                        name.as_str(),
+                        fieldDescr.typeDescHandle,
+                        arr,
+                        encoding,
+                    )?;
                 }
-                _RFCTYPE_RFCTYPE_STRING => {
-                    if let serde_json::Value::String(v) = v {
-                        set_chars_from_str(row_handle, name.as_str(), v.as_str())?;
-                    } else {
-                        info!("Expected string for field: {}, got {:?}", name, v);
-                    }
-                }
-                _RFCTYPE_RFCTYPE_TABLE => {
-                    if let serde_json::Value::Array(arr) = v {
-                        set_table_from_type_handle(
-                            row_handle,
-                            name.as_str(),
-                            fieldDescr.typeDescHandle,
-                            arr,
-                        )?;
-                    } else {
-                        info!("Expected array for field: {}, got {:?}", name, v);
-                    }
-                }
-                _ => todo!("Unsupported field type: {}", fieldDescr.type_),
+                (typ, v) => convert::set_value(
+                    row_handle,
+                    name.as_str(),
+                    typ,
+                    fieldDescr.nucLength,
+                    convert::from_json(&name, v)?,
+                    encoding,
+                )?,
             }
         }
     })
@@ -403,8 +473,9 @@ fn fill_structure(
 /// * `name` - The name of the table to set.
 /// * `type_handle` - The type handle of the table.     
 /// * `value` - A slice of JSON values representing the table rows.
+/// * `encoding` - The encoding of strings given for RAW and XSTRING fields.
 /// # Returns
-/// * `Result<(), String>` - Returns Ok(()) on success, or an error message on failure.
+/// * `Result<(), RfcError>` - Returns Ok(()) on success, or the SDK error on failure.
 /// # Errors
 /// * Returns an error if the field type is unsupported or if setting the structure fails.
 fn set_table_from_type_handle(
@@ -412,14 +483,21 @@ fn set_table_from_type_handle(
     name: &str,
     type_handle: RFC_TYPE_DESC_HANDLE,
     value: &[serde_json::Value],
-) -> Result<(), String> {
+    encoding: value::BytesEncoding,
+) -> Result<(), RfcError> {
     let mut errorInfo = error_info();
     let table_handle = unsafe { RfcCreateTable(type_handle, &mut errorInfo) };
+    if table_handle.is_null() {
+        return Err(RfcError::from(errorInfo));
+    }
 
     for v in value {
         if let serde_json::Value::Object(obj) = v {
             let row_handle = unsafe { RfcAppendNewRow(table_handle, &mut errorInfo) };
-            fill_structure(row_handle, type_handle, obj)?;
+            if row_handle.is_null() {
+                return Err(RfcError::from(errorInfo));
+            }
+            fill_structure(row_handle, type_handle, obj, encoding)?;
         } else {
             info!("Expected object for field: {}, got {:?}", name, v);
         }
@@ -429,9 +507,7 @@ fn set_table_from_type_handle(
     let name_sap = SapString::from(name);
     let rc = unsafe { RfcSetTable(cont, name_sap.raw_pointer(), table_handle, &mut errorInfo) };
     if rc != 0 {
-        let x = SapString::from(errorInfo.message.as_slice());
-        return Err(String::from(&x));
+        return Err(RfcError::from(errorInfo));
     }
-    assert_eq!(0, rc);
     Ok(())
 }
@@ -0,0 +1,464 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use log::{trace, warn};
+
+use crate::{
+    any_to_error,
+    config::ConnectionConfig,
+    connection::Connection,
+    error::{ErrorClass, RfcError},
+    rfc_param::RfcParam,
+};
+
+/// Limits and health checks of a `ConnectionPool`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    max_size: usize,
+    min_idle: usize,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    checkout_timeout: Duration,
+    ping_on_checkout: bool,
+}
+
+impl Default for PoolConfig {
+    /// Up to 10 connections, idle connections are closed after 10 minutes and every
+    /// connection after one hour, waiting for a connection times out after 30 seconds.
+    fn default() -> Self {
+        Self {
+            max_size: 10,
+            min_idle: 0,
+            idle_timeout: Some(Duration::from_secs(600)),
+            max_lifetime: Some(Duration::from_secs(3600)),
+            checkout_timeout: Duration::from_secs(30),
+            ping_on_checkout: true,
+        }
+    }
+}
+
+impl PoolConfig {
+    /// Sets the maximum number of open connections, including the ones in use.
+    pub fn max_size(mut self, n: usize) -> Self {
+        self.max_size = n.max(1);
+        self
+    }
+
+    /// Sets the number of idle connections [`ConnectionPool::maintain`] keeps open.
+    pub fn min_idle(mut self, n: usize) -> Self {
+        self.min_idle = n;
+        self
+    }
+
+    /// Sets how long a connection may stay unused before it is closed, `None` keeps it forever.
+    pub fn idle_timeout(mut self, d: Option<Duration>) -> Self {
+        self.idle_timeout = d;
+        self
+    }
+
+    /// Sets how long a connection is used before it is replaced by a new one,
+    /// `None` keeps it forever.
+    pub fn max_lifetime(mut self, d: Option<Duration>) -> Self {
+        self.max_lifetime = d;
+        self
+    }
+
+    /// Sets how long [`ConnectionPool::get`] waits for a free connection.
+    pub fn checkout_timeout(mut self, d: Duration) -> Self {
+        self.checkout_timeout = d;
+        self
+    }
+
+    /// Sets if an idle connection is pinged before it is handed out. A connection that was
+    /// used before is reset instead, which checks it as well.
+    pub fn ping_on_checkout(mut self, ping: bool) -> Self {
+        self.ping_on_checkout = ping;
+        self
+    }
+}
+
+/// Number of connections of a pool, as returned by [`ConnectionPool::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// Open connections, idle or in use.
+    pub open: usize,
+    /// Open connections that are not in use.
+    pub idle: usize,
+    /// Threads waiting for a connection.
+    pub waiting: usize,
+}
+
+struct IdleConnection {
+    connection: Connection,
+    created: Instant,
+    last_used: Instant,
+    /// The user context has to be reset before the connection is handed out again.
+    used: bool,
+}
+
+#[derive(Default)]
+struct PoolState {
+    idle: VecDeque<IdleConnection>,
+    open: usize,
+    waiting: VecDeque<u64>,
+    next_ticket: u64,
+}
+
+struct PoolInner {
+    params: Vec<RfcParam>,
+    config: PoolConfig,
+    state: Mutex<PoolState>,
+    available: Condvar,
+}
+
+/// A pool of open connections to one SAP system.
+/// All connections of a pool are opened with the same parameters, so a pool stands for
+/// one destination or one `ConnectionConfig`. Use a `PoolRegistry` to share pools by name.
+/// Threads waiting for a connection are served in the order they called [`ConnectionPool::get`].
+/// The pool can be cloned cheaply, all clones share the same connections.
+#[derive(Clone)]
+pub struct ConnectionPool {
+    inner: Arc<PoolInner>,
+}
+
+impl ConnectionPool {
+    /// Creates an empty pool that opens connections with the parameters of `template`.
+    /// The template is not connected by the pool and its parameters are validated by
+    /// the first connect.
+    pub fn new(template: &Connection, config: PoolConfig) -> Self {
+        Self {
+            inner: Arc::new(PoolInner {
+                params: template.rfc_params().to_vec(),
+                config,
+                state: Mutex::new(PoolState::default()),
+                available: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Creates a pool for a destination in the `sapnwrfc.ini` file.
+    pub fn for_destination(dest: &str, config: PoolConfig) -> Self {
+        Self::new(&Connection::new().destination(dest), config)
+    }
+
+    /// Creates a pool for a system of a profile file.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the password cannot be read.
+    pub fn for_config(c: &ConnectionConfig, config: PoolConfig) -> Result<Self, RfcError> {
+        Ok(Self::new(&c.connection()?, config))
+    }
+
+    /// Returns the current number of open, idle and waiting connections.
+    pub fn status(&self) -> Result<PoolStatus, RfcError> {
+        let state = self.lock()?;
+        Ok(PoolStatus {
+            open: state.open,
+            idle: state.idle.len(),
+            waiting: state.waiting.len(),
+        })
+    }
+
+    /// Takes a connection from the pool.
+    /// An idle connection is reused if there is one, otherwise a new connection is opened
+    /// as long as the pool has not reached its maximum size. If it has, the call waits
+    /// until another thread returns a connection.
+    /// # Returns
+    /// * `Result<PooledConnection, RfcError>` - A guard that returns the connection to the
+    ///   pool when it is dropped.
+    /// # Errors
+    /// * Returns `RfcError::Internal` if no connection became available within the
+    ///   checkout timeout.
+    /// * Returns the error of `Connection::connect` if a new connection could not be opened.
+    pub fn get(&self) -> Result<PooledConnection, RfcError> {
+        let config = &self.inner.config;
+        let deadline = Instant::now() + config.checkout_timeout;
+        // expired connections are closed after the lock is released
+        let mut expired = Vec::new();
+        let mut state = self.lock()?;
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.waiting.push_back(ticket);
+        let reused = loop {
+            if state.waiting.front() == Some(&ticket) {
+                if let Some(idle) = self.take_idle(&mut state, &mut expired) {
+                    break Some(idle);
+                }
+                if state.open < config.max_size {
+                    state.open += 1;
+                    break None;
+                }
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                state.waiting.retain(|t| *t != ticket);
+                self.inner.available.notify_all();
+                return Err(RfcError::Internal(format!(
+                    "no pooled connection available within {:?}",
+                    config.checkout_timeout
+                )));
+            }
+            state = self
+                .inner
+                .available
+                .wait_timeout(state, deadline - now)
+                .map_err(any_to_error)?
+                .0;
+        };
+        // the slot is reserved, let the next thread in line try its luck
+        state.waiting.pop_front();
+        self.inner.available.notify_all();
+        drop(state);
+        drop(expired);
+
+        if let Some(idle) = reused {
+            // the next user must not see the function group state or uncommitted work
+            let checked = if idle.used {
+                idle.connection.reset_server_context().is_ok()
+            } else {
+                !config.ping_on_checkout || idle.connection.ping().is_ok()
+            };
+            if checked {
+                return Ok(self.guard(idle.connection, idle.created));
+            }
+            warn!("discarding pooled connection that failed the check");
+        }
+        match self.open_connection() {
+            Ok(connection) => Ok(self.guard(connection, Instant::now())),
+            Err(e) => {
+                self.release_slot();
+                Err(e)
+            }
+        }
+    }
+
+    /// Closes idle connections that exceeded the idle timeout or the maximum lifetime,
+    /// and opens new ones until the pool has at least `min_idle` idle connections.
+    /// Call this periodically to keep a pool warm.
+    pub fn maintain(&self) -> Result<(), RfcError> {
+        let config = &self.inner.config;
+        let mut expired = Vec::new();
+        let mut state = self.lock()?;
+        let now = Instant::now();
+        let mut kept = VecDeque::new();
+        while let Some(idle) = state.idle.pop_front() {
+            let idle_expired = config
+                .idle_timeout
+                .is_some_and(|t| now - idle.last_used >= t)
+                && state.idle.len() + kept.len() >= config.min_idle;
+            if idle_expired || self.is_expired(&idle) {
+                trace!("closing expired pooled connection");
+                state.open -= 1;
+                expired.push(idle);
+            } else {
+                kept.push_back(idle);
+            }
+        }
+        state.idle = kept;
+        let missing = config
+            .min_idle
+            .saturating_sub(state.idle.len())
+            .min(config.max_size.saturating_sub(state.open));
+        state.open += missing;
+        drop(state);
+        drop(expired);
+
+        for _ in 0..missing {
+            match self.open_connection() {
+                Ok(connection) => {
+                    let now = Instant::now();
+                    self.lock()?.idle.push_front(IdleConnection {
+                        connection,
+                        created: now,
+                        last_used: now,
+                        used: false,
+                    });
+                    self.inner.available.notify_all();
+                }
+                Err(e) => {
+                    self.release_slot();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, PoolState>, RfcError> {
+        self.inner.state.lock().map_err(any_to_error)
+    }
+
+    /// Locks the pool state even if another thread panicked while holding the lock,
+    /// so a returned connection or a released slot is never lost.
+    fn lock_unpoisoned(&self) -> MutexGuard<'_, PoolState> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes the most recently used idle connection that has not expired.
+    /// Expired connections are moved to `expired`, to be closed by the caller.
+    fn take_idle(
+        &self,
+        state: &mut PoolState,
+        expired: &mut Vec<IdleConnection>,
+    ) -> Option<IdleConnection> {
+        while let Some(idle) = state.idle.pop_back() {
+            let idle_expired = self
+                .inner
+                .config
+                .idle_timeout
+                .is_some_and(|t| idle.last_used.elapsed() >= t);
+            if idle_expired || self.is_expired(&idle) {
+                trace!("closing expired pooled connection");
+                state.open -= 1;
+                expired.push(idle);
+                continue;
+            }
+            return Some(idle);
+        }
+        None
+    }
+
+    fn is_expired(&self, idle: &IdleConnection) -> bool {
+        self.inner
+            .config
+            .max_lifetime
+            .is_some_and(|t| idle.created.elapsed() >= t)
+    }
+
+    fn open_connection(&self) -> Result<Connection, RfcError> {
+        trace!("opening pooled connection");
+        self.inner
+            .params
+            .iter()
+            .cloned()
+            .fold(Connection::new(), |c, p| c.param(p))
+            .connect()
+    }
+
+    /// Gives up the slot of a connection that was discarded or could not be opened.
+    fn release_slot(&self) {
+        self.lock_unpoisoned().open -= 1;
+        self.inner.available.notify_all();
+    }
+
+    fn guard(&self, connection: Connection, created: Instant) -> PooledConnection {
+        PooledConnection {
+            connection: Some(connection),
+            created,
+            broken: false,
+            pool: self.clone(),
+        }
+    }
+}
+
+/// A connection taken from a `ConnectionPool`.
+/// Dereferences to the `Connection`. When the guard is dropped, the connection goes back
+/// to the pool, unless it was marked as broken, its handle became invalid after a
+/// communication failure or it exceeded the maximum lifetime. In these cases it is closed.
+/// Its user context is reset when it is handed out the next time, so dropping the guard
+/// does not wait for the SAP system.
+pub struct PooledConnection {
+    connection: Option<Connection>,
+    created: Instant,
+    broken: bool,
+    pool: ConnectionPool,
+}
+
+impl PooledConnection {
+    /// Marks the connection as unusable, so it is closed instead of returned to the pool.
+    pub fn mark_broken(&mut self) {
+        self.broken = true;
+    }
+
+    /// Passes a result through and marks the connection as broken if it is a
+    /// communication failure.
+    pub fn check<T>(&mut self, result: Result<T, RfcError>) -> Result<T, RfcError> {
+        if let Err(e) = &result {
+            if e.class() == ErrorClass::Communication {
+                self.broken = true;
+            }
+        }
+        result
+    }
+}
+
+impl Deref for PooledConnection {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection
+            .as_ref()
+            .expect("connection is only taken on drop")
+    }
+}
+
+impl DerefMut for PooledConnection {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.connection
+            .as_mut()
+            .expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConnection {
+    fn drop(&mut self) {
+        let Some(connection) = self.connection.take() else {
+            return;
+        };
+        let idle = IdleConnection {
+            connection,
+            created: self.created,
+            last_used: Instant::now(),
+            used: true,
+        };
+        let reusable = !self.broken && idle.connection.is_alive() && !self.pool.is_expired(&idle);
+        if !reusable {
+            trace!("closing pooled connection instead of returning it");
+            drop(idle);
+            self.pool.release_slot();
+            return;
+        }
+        self.pool.lock_unpoisoned().idle.push_back(idle);
+        self.pool.inner.available.notify_all();
+    }
+}
+
+/// Pools shared by name, e.g. one per destination, all created with the same `PoolConfig`.
+pub struct PoolRegistry {
+    config: PoolConfig,
+    pools: Mutex<HashMap<String, ConnectionPool>>,
+}
+
+impl PoolRegistry {
+    /// Creates an empty registry. Pools are created on first use with the given configuration.
+    pub fn new(config: PoolConfig) -> Self {
+        Self {
+            config,
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the pool with the given name, creating it from the connection returned by
+    /// `template` if it does not exist yet.
+    pub fn pool<F>(&self, name: &str, template: F) -> Result<ConnectionPool, RfcError>
+    where
+        F: FnOnce() -> Connection,
+    {
+        let mut pools = self.pools.lock().map_err(any_to_error)?;
+        let pool = pools
+            .entry(name.to_string())
+            .or_insert_with(|| ConnectionPool::new(&template(), self.config.clone()));
+        Ok(pool.clone())
+    }
+
+    /// Returns the pool for a destination in the `sapnwrfc.ini` file.
+    pub fn destination(&self, dest: &str) -> Result<ConnectionPool, RfcError> {
+        self.pool(dest, || Connection::new().destination(dest))
+    }
+}
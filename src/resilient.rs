@@ -0,0 +1,183 @@
+use std::time::Duration;
+
+use log::{info, warn};
+
+use crate::{
+    connection::Connection,
+    error::{ErrorClass, RfcError},
+    function::Function,
+};
+
+/// Controls how often and how fast an idempotent call is retried after a communication failure.
+/// The delay before retry `n` (starting at 0) is `initial_backoff * multiplier^n`,
+/// limited to `max_backoff`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    /// Three retries, waiting 500ms, 1s and 2s.
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a policy that never retries.
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the maximum number of retries after the first attempt.
+    pub fn max_retries(mut self, n: u32) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn initial_backoff(mut self, d: Duration) -> Self {
+        self.initial_backoff = d;
+        self
+    }
+
+    /// Sets the upper limit of the delay between two retries.
+    pub fn max_backoff(mut self, d: Duration) -> Self {
+        self.max_backoff = d;
+        self
+    }
+
+    /// Sets the factor the delay grows with after each retry. Values below 1 are treated as 1.
+    pub fn multiplier(mut self, m: f64) -> Self {
+        self.multiplier = m.max(1.0);
+        self
+    }
+
+    /// Returns the delay before the retry with the given number, starting at 0.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.powi(retry.min(i32::MAX as u32) as i32);
+        let secs = self.initial_backoff.as_secs_f64() * factor;
+        if secs.is_finite() && secs < self.max_backoff.as_secs_f64() {
+            Duration::from_secs_f64(secs)
+        } else {
+            self.max_backoff
+        }
+    }
+}
+
+/// A connection that survives network interruptions and restarts of the SAP system.
+/// After a communication failure the session is reopened with the stored connection
+/// parameters before the next call. Calls made with [`ResilientConnection::call_idempotent`]
+/// are repeated according to the `RetryPolicy`; calls made with [`ResilientConnection::call`]
+/// are never repeated, because the failed call may already have been executed by the system.
+/// Logon failures and ABAP errors are returned right away.
+pub struct ResilientConnection {
+    connection: Connection,
+    policy: RetryPolicy,
+}
+
+impl ResilientConnection {
+    /// Wraps a connection with the default `RetryPolicy`.
+    /// The connection does not have to be connected yet, it is opened by the first call.
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Sets the retry policy for idempotent calls.
+    pub fn policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the wrapped connection.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Calls a function module once. `prepare` is called to set the parameters before the
+    /// function is executed and `read` to read the results afterwards.
+    /// If the connection is broken, it is reopened before the call, but a call that fails
+    /// with a communication failure is not repeated.
+    /// # Returns
+    /// * `Result<T, RfcError>` - Returns the result of `read`.
+    /// # Errors
+    /// * Returns the error of the reconnect, of `prepare`, of the execution or of `read`.
+    pub fn call<T, P, R>(&mut self, name: &str, prepare: P, read: R) -> Result<T, RfcError>
+    where
+        P: Fn(&Function) -> Result<(), RfcError>,
+        R: Fn(&Function) -> Result<T, RfcError>,
+    {
+        self.try_call(name, &prepare, &read)
+    }
+
+    /// Calls a function module like [`ResilientConnection::call`], but repeats the call after
+    /// a communication failure according to the retry policy.
+    /// Only use this for function modules that can safely be executed more than once,
+    /// e.g. because they only read data.
+    /// # Errors
+    /// * Returns the last communication failure if all retries failed, or the first error
+    ///   of any other class.
+    pub fn call_idempotent<T, P, R>(
+        &mut self,
+        name: &str,
+        prepare: P,
+        read: R,
+    ) -> Result<T, RfcError>
+    where
+        P: Fn(&Function) -> Result<(), RfcError>,
+        R: Fn(&Function) -> Result<T, RfcError>,
+    {
+        let mut retry = 0;
+        loop {
+            match self.try_call(name, &prepare, &read) {
+                Err(e)
+                    if e.class() == ErrorClass::Communication
+                        && retry < self.policy.max_retries =>
+                {
+                    let delay = self.policy.delay(retry);
+                    warn!("{name} failed with {e}, retrying in {delay:?}");
+                    std::thread::sleep(delay);
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn try_call<T, P, R>(&mut self, name: &str, prepare: &P, read: &R) -> Result<T, RfcError>
+    where
+        P: Fn(&Function) -> Result<(), RfcError>,
+        R: Fn(&Function) -> Result<T, RfcError>,
+    {
+        if !self.connection.is_connected() {
+            info!("reconnecting");
+            self.connection.reopen()?;
+        }
+        let result = self.connection.function(name).and_then(|f| {
+            prepare(&f)?;
+            f.execute()?;
+            read(&f)
+        });
+        if let Err(e) = &result {
+            if e.class() == ErrorClass::Communication {
+                // the session is gone, the next call has to open a new one
+                self.connection.close();
+            }
+        }
+        result
+    }
+}
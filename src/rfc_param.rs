@@ -1,9 +1,23 @@
-use crate::string::SapString;
+use std::fmt::Debug;
 
-#[derive(Debug, Clone)]
+use crate::{
+    secret::{Secret, MASK},
+    string::SapString,
+};
+
+/// Names of connection parameters whose values are confidential.
+const SECRET_PARAMS: [&str; 4] = ["passwd", "password", "mysapsso2", "proxy_passwd"];
+
+/// Returns `true` if the value of the named connection parameter is confidential.
+pub(crate) fn is_secret_name(name: &str) -> bool {
+    SECRET_PARAMS.iter().any(|s| s.eq_ignore_ascii_case(name))
+}
+
+#[derive(Clone)]
 pub struct RfcParam {
 name: SapString,
 value: SapString,
+secret: bool,
 }
 
 impl RfcParam {
@@ -12,9 +26,25 @@ where
     S1: Into<SapString>,
     S2: Into<SapString>,
 {
+    let name = name.into();
+    let secret = is_secret_name(&String::from(&name));
     Self {
-        name: name.into(),
+        name,
         value: value.into(),
+        secret,
+    }
+}
+
+/// Creates a parameter with a confidential value, which is wiped from memory on drop
+/// and masked in debug output.
+pub fn secret<S>(name: S, value: &Secret) -> Self
+where
+    S: Into<SapString>,
+{
+    Self {
+        name: name.into(),
+        value: SapString::from(value.expose()),
+        secret: true,
     }
 }
 
@@ -25,4 +55,30 @@ pub fn name(&self) -> &SapString {
 pub fn value(&self) -> &SapString {
     &self.value
 }
+
+/// Returns `true` if the value of the parameter is confidential.
+pub fn is_secret(&self) -> bool {
+    self.secret
+}
+}
+
+impl Debug for RfcParam {
+fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut d = f.debug_struct("RfcParam");
+    d.field("name", &self.name);
+    if self.secret {
+        d.field("value", &MASK)
+    } else {
+        d.field("value", &self.value)
+    }
+    .finish()
+}
+}
+
+impl Drop for RfcParam {
+fn drop(&mut self) {
+    if self.secret {
+        self.value.zeroize();
+    }
+}
 }
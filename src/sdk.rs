@@ -0,0 +1,330 @@
+use std::{fmt::Display, path::PathBuf, sync::Mutex, time::Duration};
+
+use lazy_static::lazy_static;
+use log::{trace, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    any_to_error, check,
+    error::RfcError,
+    librfc::{
+        RfcGetVersion, RfcSetCpicKeepalive, RfcSetGlobalLogonTimeout, RfcSetIniPath,
+        RfcSetMaximumCpicConversations, RfcSetMaximumTraceFileSize,
+        RfcSetMessageServerResponseTimeout, RfcSetTraceDir, RfcSetTraceEncoding, RfcSetTraceLevel,
+        RfcSetTraceType, SAP_UC,
+    },
+    string::SapString,
+    websocket, INI_PATH_INITIALIZED,
+};
+
+/// How the SDK splits its trace files (`RFC_TRACE_TYPE`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceType {
+    /// One trace file per process.
+    Process,
+    /// One trace file per thread.
+    Thread,
+}
+
+/// Character encoding of the trace files (`RFC_TRACE_ENCODING`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TraceEncoding {
+    Utf8,
+    Utf16,
+    /// The default encoding of the operating system.
+    Default,
+}
+
+/// Version of the loaded SAP NW RFC SDK library, as returned by [`sdk_version`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SdkVersion {
+    /// Release, e.g. 7500 for 7.50.
+    pub major: u32,
+    pub minor: u32,
+    /// Patch level.
+    pub patch: u32,
+    /// Version information as reported by the library.
+    pub version: String,
+}
+
+impl SdkVersion {
+    /// Returns `true` if this version is the given one or newer.
+    pub fn at_least(&self, major: u32, minor: u32, patch: u32) -> bool {
+        (self.major, self.minor, self.patch) >= (major, minor, patch)
+    }
+}
+
+impl Display for SdkVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Returns the version of the loaded SAP NW RFC SDK library.
+pub fn sdk_version() -> SdkVersion {
+    let (mut major, mut minor, mut patch) = (0, 0, 0);
+    let version = unsafe {
+        let p = RfcGetVersion(&mut major, &mut minor, &mut patch);
+        if p.is_null() {
+            String::new()
+        } else {
+            let mut len = 0;
+            while *p.add(len) != 0 {
+                len += 1;
+            }
+            String::from(&SapString::from(std::slice::from_raw_parts(p, len)))
+        }
+    };
+    SdkVersion {
+        major,
+        minor,
+        patch,
+        version,
+    }
+}
+
+lazy_static! {
+    /// The minimum version of the last applied [`SdkConfig`] that sets one.
+    static ref MIN_VERSION: Mutex<Option<(u32, u32, u32, VersionPolicy)>> = Mutex::new(None);
+}
+
+/// What [`SdkConfig::apply`] and opening a connection do if the loaded SDK is older than
+/// the minimum version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionPolicy {
+    /// Log a warning and continue.
+    Warn,
+    /// Fail with `RfcError::Config`.
+    Refuse,
+}
+
+/// Process-wide settings of the SDK, that are otherwise taken from the `DEFAULT` section
+/// of the `sapnwrfc.ini` file. Only the settings that are set are changed.
+///
+/// ```no_run
+/// use librfc_rust::sdk::SdkConfig;
+///
+/// SdkConfig::default()
+///     .trace_level(2)
+///     .trace_dir("/var/log/sap")
+///     .apply()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdkConfig {
+    trace_level: Option<u8>,
+    trace_dir: Option<PathBuf>,
+    trace_type: Option<TraceType>,
+    trace_encoding: Option<TraceEncoding>,
+    max_trace_file_size: Option<u32>,
+    cpic_keepalive: Option<Duration>,
+    max_cpic_conversations: Option<u32>,
+    logon_timeout: Option<Duration>,
+    message_server_response_timeout: Option<Duration>,
+    websocket_ping_interval: Option<Duration>,
+    websocket_pong_timeout: Option<Duration>,
+    min_version: Option<(u32, u32, u32, VersionPolicy)>,
+}
+
+impl SdkConfig {
+    /// Sets the global trace level from 0 (off) to 4 (full), like `RFC_TRACE`.
+    pub fn trace_level(mut self, level: u8) -> Self {
+        self.trace_level = Some(level);
+        self
+    }
+
+    /// Sets the directory of new trace files, like `RFC_TRACE_DIR`.
+    /// The directory has to exist.
+    pub fn trace_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.trace_dir = Some(dir.into());
+        self
+    }
+
+    /// Sets if trace files are written per process or per thread, like `RFC_TRACE_TYPE`.
+    pub fn trace_type(mut self, trace_type: TraceType) -> Self {
+        self.trace_type = Some(trace_type);
+        self
+    }
+
+    /// Sets the character encoding of new trace files, like `RFC_TRACE_ENCODING`.
+    pub fn trace_encoding(mut self, encoding: TraceEncoding) -> Self {
+        self.trace_encoding = Some(encoding);
+        self
+    }
+
+    /// Sets the size in megabytes after which a trace file is rolled over,
+    /// like `RFC_TRACE_MAX_FILE_SIZE`. The SDK does not go below 20 MB.
+    pub fn max_trace_file_size(mut self, megabytes: u32) -> Self {
+        self.max_trace_file_size = Some(megabytes);
+        self
+    }
+
+    /// Sets the interval of the CPIC keepalive between 10 seconds and one hour,
+    /// zero turns it off.
+    pub fn cpic_keepalive(mut self, interval: Duration) -> Self {
+        self.cpic_keepalive = Some(interval);
+        self
+    }
+
+    /// Raises the maximum number of concurrent CPIC conversations, like
+    /// `MAX_CPIC_CONVERSATIONS`. The default is 203; smaller numbers are ignored.
+    pub fn max_cpic_conversations(mut self, n: u32) -> Self {
+        self.max_cpic_conversations = Some(n);
+        self
+    }
+
+    /// Sets how long the logon in the SAP system may take, between 1 second and one hour,
+    /// like `RFC_GLOBAL_LOGON_TIMEOUT`. The default is 60 seconds.
+    pub fn logon_timeout(mut self, timeout: Duration) -> Self {
+        self.logon_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how long to wait for the message server during a load-balanced logon.
+    pub fn message_server_response_timeout(mut self, timeout: Duration) -> Self {
+        self.message_server_response_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the keep alive ping interval of WebSocket connections,
+    /// see [`crate::websocket::set_ping_interval`].
+    pub fn websocket_ping_interval(mut self, interval: Duration) -> Self {
+        self.websocket_ping_interval = Some(interval);
+        self
+    }
+
+    /// Sets the timeout for the reply to a WebSocket keep alive ping,
+    /// see [`crate::websocket::set_pong_timeout`].
+    pub fn websocket_pong_timeout(mut self, timeout: Duration) -> Self {
+        self.websocket_pong_timeout = Some(timeout);
+        self
+    }
+
+    /// Requires at least the given SDK version, e.g. `7500, 0, 10` for 7.50 patch level 10.
+    /// The version is checked when the settings are applied and, once applied, before every
+    /// connection is opened. A warning is only logged once.
+    pub fn min_version(
+        mut self,
+        major: u32,
+        minor: u32,
+        patch: u32,
+        policy: VersionPolicy,
+    ) -> Self {
+        self.min_version = Some((major, minor, patch, policy));
+        self
+    }
+
+    /// Applies the settings to the SDK.
+    /// The `sapnwrfc.ini` file given by `RFC_INI` is loaded first, so these settings take
+    /// precedence over its `DEFAULT` section. Settings affect all connections of the
+    /// process, including the ones that are already open.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the SDK is older than the minimum version and the
+    ///   policy is [`VersionPolicy::Refuse`].
+    /// * Returns the SDK error of the first setting that is rejected, e.g. a value out of
+    ///   range or a trace directory that does not exist.
+    pub fn apply(&self) -> Result<(), RfcError> {
+        if let Some(min_version) = self.min_version {
+            *MIN_VERSION.lock().map_err(any_to_error)? = Some(min_version);
+        }
+        initialize()?;
+        if let Some(level) = self.trace_level {
+            check(|e| unsafe {
+                RfcSetTraceLevel(
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    level as cty::c_uint,
+                    e,
+                )
+            })?;
+        }
+        if let Some(dir) = &self.trace_dir {
+            let dir = SapString::from(dir.to_string_lossy().as_ref());
+            check(|e| unsafe { RfcSetTraceDir(dir.raw_pointer() as *mut SAP_UC, e) })?;
+        }
+        if let Some(trace_type) = self.trace_type {
+            let trace_type = SapString::from(match trace_type {
+                TraceType::Process => "PROCESS",
+                TraceType::Thread => "THREAD",
+            });
+            check(|e| unsafe { RfcSetTraceType(trace_type.raw_pointer() as *mut SAP_UC, e) })?;
+        }
+        if let Some(encoding) = self.trace_encoding {
+            let encoding = SapString::from(match encoding {
+                TraceEncoding::Utf8 => "UTF-8",
+                TraceEncoding::Utf16 => "UTF-16",
+                TraceEncoding::Default => "DEFAULT",
+            });
+            check(|e| unsafe { RfcSetTraceEncoding(encoding.raw_pointer() as *mut SAP_UC, e) })?;
+        }
+        if let Some(size) = self.max_trace_file_size {
+            check(|e| unsafe { RfcSetMaximumTraceFileSize(size, 'M' as SAP_UC, e) })?;
+        }
+        if let Some(interval) = self.cpic_keepalive {
+            check(|e| unsafe { RfcSetCpicKeepalive(seconds(interval), e) })?;
+        }
+        if let Some(n) = self.max_cpic_conversations {
+            check(|e| unsafe { RfcSetMaximumCpicConversations(n, e) })?;
+        }
+        if let Some(timeout) = self.logon_timeout {
+            check(|e| unsafe { RfcSetGlobalLogonTimeout(seconds(timeout), e) })?;
+        }
+        if let Some(timeout) = self.message_server_response_timeout {
+            check(|e| unsafe { RfcSetMessageServerResponseTimeout(seconds(timeout), e) })?;
+        }
+        if let Some(interval) = self.websocket_ping_interval {
+            websocket::set_ping_interval(interval)?;
+        }
+        if let Some(timeout) = self.websocket_pong_timeout {
+            websocket::set_pong_timeout(timeout)?;
+        }
+        trace!("SDK settings applied: {self:?}");
+        Ok(())
+    }
+}
+
+/// Loads the `sapnwrfc.ini` file from the directory given by the `RFC_INI` environment
+/// variable. This is done once, before the first connection is opened or the first
+/// `SdkConfig` is applied. The minimum SDK version is checked every time.
+pub(crate) fn initialize() -> Result<(), RfcError> {
+    check_min_version()?;
+    let mut ini_initialized = INI_PATH_INITIALIZED.lock().map_err(any_to_error)?;
+    if !*ini_initialized {
+        if let Ok(rfc_ini_path) = std::env::var("RFC_INI") {
+            let ini_path = SapString::from(rfc_ini_path);
+            check(|e| unsafe { RfcSetIniPath(ini_path.raw_pointer(), e) })?;
+            trace!("RFC INI path set to: {:?}", ini_path);
+        }
+        *ini_initialized = true;
+    }
+    Ok(())
+}
+
+/// Checks the loaded SDK against the minimum version set by [`SdkConfig::min_version`].
+fn check_min_version() -> Result<(), RfcError> {
+    let mut min_version = MIN_VERSION.lock().map_err(any_to_error)?;
+    if let Some((major, minor, patch, policy)) = *min_version {
+        let version = sdk_version();
+        if !version.at_least(major, minor, patch) {
+            let message = format!(
+                "SAP NW RFC SDK {version} is older than the required {major}.{minor}.{patch}"
+            );
+            match policy {
+                VersionPolicy::Warn => {
+                    warn!("{message}");
+                    // the loaded library does not change, warn only once
+                    *min_version = None;
+                }
+                VersionPolicy::Refuse => return Err(RfcError::Config(message)),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts a duration to whole seconds for the SDK.
+pub(crate) fn seconds(d: Duration) -> cty::c_uint {
+    d.as_secs().min(cty::c_uint::MAX as u64) as cty::c_uint
+}
@@ -0,0 +1,144 @@
+use std::{fmt::Debug, path::PathBuf, process::Command};
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::RfcError;
+
+/// Replacement text for secret values in debug, trace and listing output.
+pub const MASK: &str = "********";
+
+/// A password or another confidential value, like a logon ticket.
+/// The memory of the value is overwritten when the secret is dropped, and the value
+/// never shows up in `Debug` output or when the secret is serialized.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(Zeroizing<String>);
+
+impl Secret {
+    /// Wraps a value as secret.
+    pub fn new<S: Into<String>>(value: S) -> Self {
+        Self(Zeroizing::new(value.into()))
+    }
+
+    /// Reads the secret from an environment variable.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the variable is not set.
+    pub fn from_env(name: &str) -> Result<Self, RfcError> {
+        std::env::var(name)
+            .map(Self::new)
+            .map_err(|_| RfcError::Config(format!("environment variable '{name}' is not set")))
+    }
+
+    /// Reads the secret from a file. A trailing line break is removed.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the file cannot be read.
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Result<Self, RfcError> {
+        let path = path.into();
+        let content = Zeroizing::new(
+            std::fs::read_to_string(&path)
+                .map_err(|e| RfcError::Config(format!("{}: {e}", path.display())))?,
+        );
+        Ok(Self::new(content.trim_end_matches(['\r', '\n'])))
+    }
+
+    /// Runs a helper command, e.g. a password manager, and uses its standard output as secret.
+    /// A trailing line break is removed.
+    /// # Arguments
+    /// * `command` - The program followed by its arguments.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the command cannot be started, fails or prints
+    ///   anything but UTF-8.
+    pub fn from_command<S: AsRef<str>>(command: &[S]) -> Result<Self, RfcError> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| RfcError::Config("empty secret command".to_string()))?;
+        let program = program.as_ref();
+        let output = Command::new(program)
+            .args(args.iter().map(|a| a.as_ref()))
+            .output()
+            .map_err(|e| RfcError::Config(format!("{program}: {e}")))?;
+        let stdout = Zeroizing::new(output.stdout);
+        if !output.status.success() {
+            return Err(RfcError::Config(format!(
+                "{program} failed with {}",
+                output.status
+            )));
+        }
+        let value = std::str::from_utf8(&stdout)
+            .map_err(|e| RfcError::Config(format!("{program}: {e}")))?;
+        Ok(Self::new(value.trim_end_matches(['\r', '\n'])))
+    }
+
+    /// Returns the secret value. Take care not to copy it into memory that is not wiped.
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret({MASK})")
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(MASK)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Where a secret comes from in a profile file: the value itself, an environment
+/// variable, a file, or the output of a helper command.
+///
+/// ```yaml
+/// passwd: secret
+/// passwd: { env: SAP_PASSWORD }
+/// passwd: { file: /run/secrets/sap }
+/// passwd: { command: [pass, show, sap/dev] }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum SecretSource {
+    Value(Secret),
+    Env { env: String },
+    File { file: PathBuf },
+    Command { command: Vec<String> },
+}
+
+impl SecretSource {
+    /// Reads the secret from its source.
+    /// # Errors
+    /// * Returns `RfcError::Config` if the source is not available.
+    pub fn resolve(&self) -> Result<Secret, RfcError> {
+        match self {
+            SecretSource::Value(secret) => Ok(secret.clone()),
+            SecretSource::Env { env } => Secret::from_env(env),
+            SecretSource::File { file } => Secret::from_file(file),
+            SecretSource::Command { command } => Secret::from_command(command),
+        }
+    }
+}
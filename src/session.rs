@@ -0,0 +1,100 @@
+use std::ops::{Deref, DerefMut};
+
+use log::{trace, warn};
+
+use crate::{connection::Connection, error::RfcError, value::Value};
+
+/// A stateful ABAP session on a connection.
+/// All calls made through the session run in the same ABAP user context, so the state of
+/// function groups, e.g. buffered BAPI changes, is kept from one call to the next until
+/// [`Session::commit`] or [`Session::rollback`]. When the session is dropped, the context is
+/// reset with `RfcResetServerContext`, so a connection that is used again, e.g. from a
+/// pool, starts with a fresh context. If the reset fails, the connection is closed.
+///
+/// ```no_run
+/// use librfc_rust::connection::Connection;
+///
+/// let mut c = Connection::new().destination("sap").connect().unwrap();
+/// let session = c.session();
+/// let f = session.function("BAPI_USER_CHANGE").unwrap();
+/// f.execute().unwrap();
+/// session.commit(true).unwrap();
+/// ```
+pub struct Session<'a> {
+    connection: &'a mut Connection,
+}
+
+impl<'a> Session<'a> {
+    pub(crate) fn new(connection: &'a mut Connection) -> Self {
+        Self { connection }
+    }
+
+    /// Commits the changes of the session by calling `BAPI_TRANSACTION_COMMIT`.
+    /// # Arguments
+    /// * `wait` - Waits until the update task has written the changes.
+    /// # Errors
+    /// * Returns `RfcError::BapiReturn` with the message of the `RETURN` parameter if the
+    ///   commit failed.
+    pub fn commit(&self, wait: bool) -> Result<(), RfcError> {
+        let f = self.connection.function("BAPI_TRANSACTION_COMMIT")?;
+        if wait {
+            f.set("WAIT", "X")?;
+        }
+        f.execute()?;
+        if let Value::Structure(r) = f.get("RETURN")? {
+            let field = |name: &str| r.get(name).map(|v| v.to_string());
+            let msg_type = field("TYPE")?;
+            if msg_type == "E" || msg_type == "A" {
+                return Err(RfcError::BapiReturn {
+                    msg_type,
+                    msg_class: field("ID")?,
+                    msg_number: field("NUMBER")?,
+                    message: field("MESSAGE")?,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Discards the changes of the session by calling `BAPI_TRANSACTION_ROLLBACK`.
+    pub fn rollback(&self) -> Result<(), RfcError> {
+        self.connection
+            .function("BAPI_TRANSACTION_ROLLBACK")?
+            .execute()
+    }
+
+    /// Resets the ABAP context now and continues with a fresh one.
+    /// # Errors
+    /// * Returns the SDK error if the context could not be reset. The connection should
+    ///   be reopened in that case.
+    pub fn reset(&mut self) -> Result<(), RfcError> {
+        self.connection.reset_server_context()
+    }
+}
+
+impl Deref for Session<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.connection
+    }
+}
+
+impl DerefMut for Session<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.connection
+    }
+}
+
+impl Drop for Session<'_> {
+    fn drop(&mut self) {
+        if !self.connection.is_connected() {
+            return;
+        }
+        trace!("resetting server context at the end of the session");
+        if let Err(e) = self.connection.reset_server_context() {
+            warn!("closing connection, server context could not be reset: {e}");
+            self.connection.close();
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::{
+    sync::mpsc::{self, Sender},
+    thread,
+};
+
+use log::{error, trace};
+
+use crate::{any_to_error, connection::Connection, error::RfcError};
+
+type Job = Box<dyn FnOnce(&mut Connection) + Send>;
+
+/// A connection that can be used from any thread.
+/// The `Connection` and all SDK handles created from it live on a dedicated worker thread.
+/// Calls are sent to the worker over a channel and are executed one after the other, the
+/// results are returned as owned values. Clones share the same worker and connection; the
+/// connection is closed when the last clone is dropped.
+#[derive(Clone)]
+pub struct SharedConnection {
+    jobs: Sender<Job>,
+}
+
+impl SharedConnection {
+    /// Starts a worker thread and connects it with the parameters of `connection`.
+    /// Only the parameters are passed to the worker, the connection itself is not connected.
+    /// # Returns
+    /// * `Result<SharedConnection, RfcError>` - Returns `Ok(SharedConnection)` if the
+    ///   connection was established.
+    /// # Errors
+    /// * Returns the error of [`Connection::connect`], or `RfcError::Internal` if the
+    ///   worker thread could not be started.
+    pub fn connect(connection: Connection) -> Result<Self, RfcError> {
+        let params = connection.rfc_params().to_vec();
+        let (jobs, rx) = mpsc::channel::<Job>();
+        let (ready, connected) = mpsc::channel();
+        thread::Builder::new()
+            .name("sap-rfc-connection".to_string())
+            .spawn(move || {
+                let connection = params
+                    .into_iter()
+                    .fold(Connection::new(), |c, p| c.param(p));
+                let mut c = match connection.connect() {
+                    Ok(c) => {
+                        let _ = ready.send(Ok(()));
+                        c
+                    }
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return;
+                    }
+                };
+                for job in rx {
+                    job(&mut c);
+                }
+                trace!("connection worker stopped");
+            })
+            .map_err(any_to_error)?;
+        connected.recv().map_err(any_to_error)??;
+        Ok(Self { jobs })
+    }
+
+    /// Runs `f` with the connection on the worker thread and returns its result.
+    /// Use this for anything the other methods do not cover; `f` must not return
+    /// values that hold SDK handles, like a `Function`.
+    /// # Errors
+    /// * Returns the error of `f`, or `RfcError::Internal` if the worker has stopped.
+    pub fn with<T, F>(&self, f: F) -> Result<T, RfcError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> Result<T, RfcError> + Send + 'static,
+    {
+        let (reply, result) = mpsc::channel();
+        self.jobs
+            .send(Box::new(move |c: &mut Connection| {
+                let _ = reply.send(f(c));
+            }))
+            .map_err(|_| RfcError::Internal("connection worker has stopped".to_string()))?;
+        result
+            .recv()
+            .map_err(|_| RfcError::Internal("connection worker has stopped".to_string()))?
+    }
+
+    /// Calls a function module with the given parameters.
+    /// The parameters are set like with `Function::set_parameters`, i.e. `parameters` is a
+    /// JSON object with the parameter names as keys.
+    /// # Returns
+    /// * `Result<serde_json::Value, RfcError>` - All parameters of the function after the
+    ///   call, serialized the same way as a `Function`.
+    /// # Errors
+    /// * Returns the error of retrieving, preparing or executing the function.
+    pub fn call(
+        &self,
+        function_name: &str,
+        parameters: &serde_json::Value,
+    ) -> Result<serde_json::Value, RfcError> {
+        let name = function_name.to_string();
+        let parameters = parameters.clone();
+        self.with(move |c| {
+            let f = c.function(&name)?;
+            f.set_parameters(&parameters)?;
+            f.execute()?;
+            serde_json::to_value(&f).map_err(|e| {
+                error!("{name}: {e}");
+                RfcError::Internal(e.to_string())
+            })
+        })
+    }
+
+    /// Sends a ping to the SAP system, see [`Connection::ping`].
+    pub fn ping(&self) -> Result<(), RfcError> {
+        self.with(|c| c.ping())
+    }
+}
@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::RfcError,
+    error_info,
+    librfc::{RfcSNCKeyToName, RfcSNCNameToKey},
+    rfc_param::RfcParam,
+    string::SapString,
+};
+
+/// Maximum length of an SNC name in characters.
+const SNC_NAME_LENGTH: usize = 256;
+
+/// Maximum length of an SNC key in bytes, as documented by the SDK.
+const SNC_KEY_LENGTH: usize = 1024;
+
+/// Quality of protection of an SNC connection (`snc_qop`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SncQop {
+    /// Authentication only.
+    Authentication,
+    /// Authentication and integrity protection.
+    Integrity,
+    /// Authentication, integrity and privacy protection (encryption).
+    Privacy,
+    /// The default protection configured with `snc/data_protection/use` on the server.
+    Default,
+    /// The maximum protection configured with `snc/data_protection/max` on the server.
+    Maximum,
+}
+
+impl SncQop {
+    /// Returns the value of the `snc_qop` parameter.
+    pub fn code(&self) -> &'static str {
+        match self {
+            SncQop::Authentication => "1",
+            SncQop::Integrity => "2",
+            SncQop::Privacy => "3",
+            SncQop::Default => "8",
+            SncQop::Maximum => "9",
+        }
+    }
+}
+
+/// Settings of a Secure Network Communication (SNC) connection.
+/// The partner name is the SNC name of the SAP system, e.g. `p:CN=ABC, O=Example, C=DE`.
+/// The own name and the GSS library default to the settings of the SNC environment.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SncConfig {
+    partner_name: String,
+    qop: Option<SncQop>,
+    my_name: Option<String>,
+    lib: Option<String>,
+    sso: Option<bool>,
+}
+
+impl SncConfig {
+    /// Creates an SNC configuration for the partner with the given SNC name.
+    pub fn new(partner_name: &str) -> Self {
+        Self {
+            partner_name: partner_name.to_string(),
+            qop: None,
+            my_name: None,
+            lib: None,
+            sso: None,
+        }
+    }
+
+    /// Sets the quality of protection (`snc_qop`).
+    pub fn qop(mut self, qop: SncQop) -> Self {
+        self.qop = Some(qop);
+        self
+    }
+
+    /// Sets the own SNC name (`snc_myname`), if it differs from the default of the
+    /// SNC environment.
+    pub fn my_name(mut self, name: &str) -> Self {
+        self.my_name = Some(name.to_string());
+        self
+    }
+
+    /// Sets the path of the GSS library (`snc_lib`), e.g. the SAP Cryptographic Library.
+    pub fn lib(mut self, path: &str) -> Self {
+        self.lib = Some(path.to_string());
+        self
+    }
+
+    /// Enables or disables the logon with the SNC identity instead of user and password
+    /// (`snc_sso`). The SDK enables it by default.
+    pub fn sso(mut self, sso: bool) -> Self {
+        self.sso = Some(sso);
+        self
+    }
+
+    /// Returns the connection parameters for this configuration, including `snc_mode`.
+    pub fn to_params(&self) -> Vec<RfcParam> {
+        let mut v = vec![
+            RfcParam::new("snc_mode", "1"),
+            RfcParam::new("snc_partnername", self.partner_name.as_str()),
+        ];
+        if let Some(qop) = self.qop {
+            v.push(RfcParam::new("snc_qop", qop.code()));
+        }
+        if let Some(name) = &self.my_name {
+            v.push(RfcParam::new("snc_myname", name.as_str()));
+        }
+        if let Some(lib) = &self.lib {
+            v.push(RfcParam::new("snc_lib", lib.as_str()));
+        }
+        if let Some(sso) = self.sso {
+            v.push(RfcParam::new("snc_sso", if sso { "1" } else { "0" }));
+        }
+        v
+    }
+}
+
+/// Converts an SNC name into the binary SNC key the GSS library uses for it.
+/// # Arguments
+/// * `lib` - The GSS library to use, or `None` for the library given by `SNC_LIB`.
+/// * `name` - The SNC name to convert.
+/// # Errors
+/// * Returns the SDK error if SNC is not available or the name is invalid.
+pub fn snc_name_to_key(lib: Option<&str>, name: &str) -> Result<Vec<u8>, RfcError> {
+    let lib = lib.map(SapString::from);
+    let name = SapString::from(name);
+    let mut key = vec![0u8; SNC_KEY_LENGTH];
+    let mut length = key.len() as cty::c_uint;
+    let mut errorInfo = error_info();
+    let rc = unsafe {
+        RfcSNCNameToKey(
+            lib.as_ref().map_or(std::ptr::null(), |l| l.raw_pointer()),
+            name.raw_pointer(),
+            key.as_mut_ptr(),
+            &mut length,
+            &mut errorInfo,
+        )
+    };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    key.truncate(length as usize);
+    Ok(key)
+}
+
+/// Converts a binary SNC key into the SNC name.
+/// # Arguments
+/// * `lib` - The GSS library to use, or `None` for the library given by `SNC_LIB`.
+/// * `key` - The SNC key to convert.
+/// # Errors
+/// * Returns the SDK error if SNC is not available or the key is invalid.
+pub fn snc_key_to_name(lib: Option<&str>, key: &[u8]) -> Result<String, RfcError> {
+    let lib = lib.map(SapString::from);
+    let mut name = [0u16; SNC_NAME_LENGTH + 1];
+    let mut errorInfo = error_info();
+    let rc = unsafe {
+        RfcSNCKeyToName(
+            lib.as_ref().map_or(std::ptr::null(), |l| l.raw_pointer()),
+            key.as_ptr(),
+            key.len() as cty::c_uint,
+            name.as_mut_ptr(),
+            name.len() as cty::c_uint,
+            &mut errorInfo,
+        )
+    };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    Ok(String::from(&SapString::from(name.as_slice())))
+}
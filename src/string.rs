@@ -1,4 +1,5 @@
 use tracing::trace;
+use zeroize::Zeroize;
 
 use crate::librfc::SAP_UC;
 
@@ -20,7 +21,12 @@ impl SapString {
     }
 
     pub fn len(&self) -> usize {
-        self.vec.len() - 1
+        self.vec.len().saturating_sub(1)
+    }
+
+    /// Overwrites the characters with zeros, used for confidential values.
+    pub(crate) fn zeroize(&mut self) {
+        self.vec.zeroize();
     }
 }
 
@@ -32,8 +38,7 @@ impl std::fmt::Debug for SapString {
 
 impl From<&str> for SapString {
     fn from(value: &str) -> Self {
-        let mut v: Vec<u16> = Vec::new();
-        ucs2::encode_with(value, |c| Ok(v.push(c))).unwrap();
+        let mut v: Vec<u16> = value.encode_utf16().collect();
         v.push(0);
         SapString::new(v)
     }
@@ -41,7 +46,6 @@ impl From<&str> for SapString {
 
 impl From<&SapString> for String {
     fn from(value: &SapString) -> Self {
-        let mut v: Vec<u8> = Vec::new();
         let mut orig = value.vec.clone();
         while orig.len() > 0 {
             let l = orig.len() - 1;
@@ -55,9 +59,8 @@ impl From<&SapString> for String {
             }
             break;
         }
-        trace!("ucs2 vector created with {} elements", orig.len());
-        ucs2::decode_with(orig.as_slice(), |c| Ok(v.extend_from_slice(c))).unwrap();
-        String::from_utf8(v).unwrap()
+        trace!("utf-16 vector created with {} elements", orig.len());
+        String::from_utf16_lossy(orig.as_slice())
     }
 }
 
@@ -78,9 +81,6 @@ impl From<&[u16]> for SapString {
 
 impl From<String> for SapString {
     fn from(value: String) -> Self {
-        let mut v: Vec<u16> = Vec::new();
-        ucs2::encode_with(value.as_str(), |c| Ok(v.push(c))).unwrap();
-        v.push(0);
-        SapString::new(v)
+        SapString::from(value.as_str())
     }
 }
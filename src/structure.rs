@@ -3,17 +3,17 @@ use log::trace;
 use serde::{ser::SerializeMap, Serialize};
 
 use crate::{
+    convert::{get_value, set_value},
+    error::RfcError,
     error_info, field_descriptor,
     librfc::{
         RfcCreateStructure, RfcDescribeType, RfcDestroyStructure, RfcGetChars, RfcGetFieldCount,
-        RfcGetFieldDescByIndex, RfcGetFieldDescByName, RfcGetInt, RfcGetInt1, RfcGetString,
-        RfcGetStructure, RfcGetTable, RfcType, RFC_STRUCTURE_HANDLE, RFC_TABLE_HANDLE,
+        RfcGetFieldDescByIndex, RfcGetFieldDescByName, RfcType, RFC_FIELD_DESC,
+        RFC_STRUCTURE_HANDLE,
         _RFCTYPE_RFCTYPE_CHAR as RFCTYPE_RFCTYPE_CHAR,
     },
-    set_chars,
     string::SapString,
-    table::SapTable,
-    value::Value,
+    value::{BytesEncoding, SerializeWith, Value},
 };
 
 pub struct SapStructure {
@@ -35,6 +35,15 @@ impl Drop for SapStructure {
 
 impl Serialize for SapStructure {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.serialize_with(serializer, BytesEncoding::default())
+    }
+}
+
+impl SerializeWith for SapStructure {
+    fn serialize_with<S>(&self, serializer: S, encoding: BytesEncoding) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
@@ -46,7 +55,7 @@ impl Serialize for SapStructure {
             match self.get(&self.fields[idx]) {
                 Ok(value) => {
                     let field = &self.fields[idx];
-                    map.serialize_entry(field, &value)?;
+                    map.serialize_entry(field, &value.with_bytes_encoding(encoding))?;
                 }
                 Err(e) => {
                     error!("failed to deserialize field {}: {}", &self.fields[idx], e);
@@ -59,25 +68,25 @@ impl Serialize for SapStructure {
 }
 
 impl SapStructure {
-    pub fn new(handle: RFC_STRUCTURE_HANDLE, dependent: bool) -> Result<Self, String> {
+    pub fn new(handle: RFC_STRUCTURE_HANDLE, dependent: bool) -> Result<Self, RfcError> {
         let mut errorInfo = error_info();
         let mut count: cty::c_uint = 0;
         let mut fields = vec![];
         unsafe {
             let type_handle = RfcDescribeType(handle, &mut errorInfo);
             if errorInfo.code != 0 {
-                return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+                return Err(RfcError::from(errorInfo));
             }
             let rc = RfcGetFieldCount(type_handle, &mut count, &mut errorInfo);
             if errorInfo.code != 0 || rc != 0 {
-                return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+                return Err(RfcError::from(errorInfo));
             }
 
             for idx in 0..count {
                 let mut fieldDescr = field_descriptor();
                 let rc = RfcGetFieldDescByIndex(type_handle, idx, &mut fieldDescr, &mut errorInfo);
                 if errorInfo.code != 0 || rc != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+                    return Err(RfcError::from(errorInfo));
                 }
                 let fld = String::from(&SapString::from(fieldDescr.name.as_slice()));
                 fields.push(fld);
@@ -91,29 +100,32 @@ impl SapStructure {
         })
     }
 
-    pub fn set<V>(&self, name: &str, value: V) -> Result<(), String>
+    /// Sets a field of the structure, converting the value into the ABAP type of the
+    /// field like `Function::set`, strings for RAW and XSTRING fields are decoded as base64.
+    pub fn set<V>(&self, name: &str, value: V) -> Result<(), RfcError>
     where
         V: Into<Value>,
     {
-        let v: Value = value.into();
-        match v {
-            Value::String(sap_string) => set_chars(self.handle, name, sap_string)?,
-            _ => todo!(),
-        }
-        Ok(())
+        let fieldDescr = self.describe(name)?;
+        let typ = RfcType::try_from(fieldDescr.type_)?;
+        set_value(
+            self.handle,
+            name,
+            typ,
+            fieldDescr.nucLength,
+            value.into(),
+            BytesEncoding::default(),
+        )
     }
 
-    pub fn get<S>(&self, name: S) -> Result<Value, String>
-    where
-        S: Into<String>,
-    {
+    /// Returns the description of the field with the given name.
+    fn describe(&self, name: &str) -> Result<RFC_FIELD_DESC, RfcError> {
         let mut errorInfo = error_info();
         let mut fieldDescr = field_descriptor();
-        let str_name: String = name.into();
-        let sap_name = SapString::from(str_name);
+        let sap_name = SapString::from(name);
         let type_handle = unsafe { RfcDescribeType(self.handle, &mut errorInfo) };
         if errorInfo.code != 0 {
-            return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+            return Err(RfcError::from(errorInfo));
         }
         let rc = unsafe {
             RfcGetFieldDescByName(
@@ -124,217 +136,40 @@ impl SapStructure {
             )
         };
         if rc != 0 {
-            return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+            return Err(RfcError::from(errorInfo));
         }
+        Ok(fieldDescr)
+    }
+
+    /// Returns the value of a field of the structure, converted like `Function::get`.
+    pub fn get<S>(&self, name: S) -> Result<Value, RfcError>
+    where
+        S: Into<String>,
+    {
+        let name: String = name.into();
+        let fieldDescr = self.describe(&name)?;
         trace!(
             "field description: {:} uc-length: {}",
             fieldDescr.type_,
             fieldDescr.ucLength
         );
-        assert_eq!(0, rc);
-        match RfcType::from(fieldDescr.type_) {
-            RfcType::Char => {
-                let mut buffer = vec![0; fieldDescr.ucLength as usize + 1];
-                let rc = unsafe {
-                    RfcGetChars(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        fieldDescr.ucLength,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                Ok(Value::String(SapString::from(buffer.as_slice())))
-            }
-            RfcType::Date => {
-                let mut buffer = vec![0; fieldDescr.ucLength as usize + 1];
-                let rc = unsafe {
-                    RfcGetChars(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        fieldDescr.ucLength,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                Ok(Value::String(SapString::from(buffer.as_slice())))
-            }
-            RfcType::Time => {
-                let mut buffer = vec![0; fieldDescr.ucLength as usize + 1];
-                let rc = unsafe {
-                    RfcGetChars(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        fieldDescr.ucLength,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                Ok(Value::String(SapString::from(buffer.as_slice())))
-            }
-            RfcType::Bcd => {
-                let mut buffer = vec![0; fieldDescr.ucLength as usize + 3];
-                let rc = unsafe {
-                    RfcGetChars(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        buffer.len() as u32,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                Ok(Value::String(SapString::from(buffer.as_slice())))
-            }
-            RfcType::Num => {
-                let mut buffer = vec![0; fieldDescr.ucLength as usize + 3];
-                let rc = unsafe {
-                    RfcGetChars(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        buffer.len() as u32,
-                        &mut errorInfo,
-                    )
-                };
-                if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                assert_eq!(0, rc);
-                Ok(Value::String(SapString::from(buffer.as_slice())))
-            }
-            RfcType::Int1 => {
-                let mut value = 0;
-                unsafe {
-                    RfcGetInt1(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        &mut value,
-                        &mut errorInfo,
-                    )
-                };
-                Ok(Value::Int(value as _))
-            }
-            RfcType::Int2 => {
-                let mut value = 0;
-                unsafe {
-                    RfcGetInt(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        &mut value,
-                        &mut errorInfo,
-                    )
-                };
-                Ok(Value::Int(value as _))
-            }
-            RfcType::Int => {
-                let mut value = 0;
-                unsafe {
-                    RfcGetInt(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        &mut value,
-                        &mut errorInfo,
-                    )
-                };
-                Ok(Value::Int(value as _))
-            }
-            RfcType::Structure => {
-                let mut structHandle = 0 as RFC_STRUCTURE_HANDLE;
-                let rc = unsafe {
-                    RfcGetStructure(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        &mut structHandle,
-                        &mut errorInfo,
-                    )
-                };
-                if rc != 0 || errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                Ok(Value::Structure(SapStructure::new(structHandle, true)?))
-            }
-            RfcType::String => {
-                let mut strlen = 0u32;
-                let mut buf0 = [0;5];
-                let _rc = unsafe {
-                    RfcGetString(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buf0.as_mut_ptr(),
-                        buf0.len() as u32,
-                        &mut strlen,
-                        &mut errorInfo,
-                    )
-                };
-                let mut buffer = vec![0; strlen as usize + 1];
-                let rc = unsafe {
-                    RfcGetString(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        buffer.as_mut_ptr(),
-                        buffer.len() as u32,
-                        std::ptr::null_mut(),
-                        &mut errorInfo,
-                    )
-                };
-
-                if errorInfo.code != 0 {
-                    let s = String::from(&SapString::from(errorInfo.message.as_slice()));
-                    return Err(s);
-                }
-                assert_eq!(0, rc);
-                Ok(Value::String(SapString::from(buffer.as_slice())))
-            }
-            RfcType::Table => {
-                trace!("getting table for field: {:?}", sap_name);
-                let mut table_handle = 0 as RFC_TABLE_HANDLE;
-                let rc = unsafe {
-                    RfcGetTable(
-                        self.handle,
-                        sap_name.raw_pointer(),
-                        &mut table_handle,
-                        &mut errorInfo,
-                    )
-                };
-                if rc != 0 || errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
-                }
-                let t = SapTable::new(table_handle, true);
-                trace!("got table: {:?}", t);
-                Ok(Value::Table(t))
-            }
-            x => Err(format!("Unsupported field type in structure: {x:?}")),
-        }
+        let typ = RfcType::try_from(fieldDescr.type_)?;
+        get_value(self.handle, &name, typ, fieldDescr.nucLength)
     }
 
-    pub fn from_type(type_handle: crate::RFC_TYPE_DESC_HANDLE) -> Self {
+    pub fn from_type(type_handle: crate::RFC_TYPE_DESC_HANDLE) -> Result<Self, RfcError> {
         trace!("creating structure from type handle");
         unsafe {
             let mut errorInfo = error_info();
             let struct_handle = RfcCreateStructure(type_handle, &mut errorInfo);
-            if errorInfo.code != 0 {
-                panic!(
-                    "Failed to create structure: {}",
-                    String::from(&SapString::from(errorInfo.message.as_slice()))
-                );
+            if errorInfo.code != 0 || struct_handle.is_null() {
+                return Err(RfcError::from(errorInfo));
+            }
+            let result = Self::new(struct_handle, false);
+            if result.is_err() {
+                RfcDestroyStructure(struct_handle, &mut errorInfo);
             }
-            Self::new(struct_handle, false).unwrap()
+            result
         }
     }
 
@@ -350,13 +185,20 @@ impl std::fmt::Debug for SapStructure {
         dbg.field("handle", &self.handle);
         unsafe {
             let type_handle = RfcDescribeType(self.handle, &mut errorInfo);
-            assert_eq!(errorInfo.code, 0);
+            if errorInfo.code != 0 {
+                return dbg.finish_non_exhaustive();
+            }
             let rc = RfcGetFieldCount(type_handle, &mut count, &mut errorInfo);
+            if rc != 0 {
+                return dbg.finish_non_exhaustive();
+            }
 
             for idx in 0..count {
                 let mut fieldDescr = field_descriptor();
                 let rc = RfcGetFieldDescByIndex(type_handle, idx, &mut fieldDescr, &mut errorInfo);
-                assert_eq!(0, rc);
+                if rc != 0 {
+                    continue;
+                }
                 let dbg_val: Box<dyn std::fmt::Debug> = match fieldDescr.type_ {
                     crate::_RFCTYPE_RFCTYPE_CHAR => {
                         let mut buffer = vec![0; fieldDescr.ucLength as usize + 1];
@@ -367,8 +209,11 @@ impl std::fmt::Debug for SapStructure {
                             fieldDescr.ucLength,
                             &mut errorInfo,
                         );
-                        assert_eq!(0, rc);
-                        Box::new(String::from(&SapString::from(buffer.as_slice())))
+                        if rc == 0 {
+                            Box::new(String::from(&SapString::from(buffer.as_slice())))
+                        } else {
+                            Box::new(String::from("<error>"))
+                        }
                     }
                     _ => Box::new(String::from("<value>")),
                 };
@@ -377,7 +222,6 @@ impl std::fmt::Debug for SapStructure {
                     &dbg_val,
                 );
             }
-            assert_eq!(0, rc);
         }
         dbg.finish()
     }
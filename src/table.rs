@@ -1,15 +1,18 @@
-use log::trace;
-use serde::{ser::SerializeSeq, Serialize};
+use log::{error, trace};
+use serde::{
+    ser::{self, SerializeSeq},
+    Serialize,
+};
 
 use crate::{
+    error::RfcError,
     error_info,
     librfc::{
         RfcAppendRow, RfcDescribeType, RfcDestroyTable, RfcGetCurrentRow, RfcGetRowCount,
         RfcMoveTo, RFC_TABLE_HANDLE,
     },
-    string::SapString,
     structure::SapStructure,
-    value::Value,
+    value::{BytesEncoding, SerializeWith, Value},
 };
 
 pub struct SapTable {
@@ -21,41 +24,62 @@ impl SapTable {
     pub fn new(handle: RFC_TABLE_HANDLE, dependent: bool) -> Self {
         Self { handle, dependent }
     }
+    /// Returns the number of rows, or 0 if the SDK cannot read it, see [`SapTable::try_len`].
     pub fn len(&self) -> usize {
-        unsafe {
-            let mut errorInfo = error_info();
-            let mut rowCount: u32 = 0;
-            let rc = RfcGetRowCount(self.handle, &mut rowCount, &mut errorInfo);
-            assert_eq!(0, rc);
-            rowCount as usize
-        }
+        self.try_len().unwrap_or_else(|e| {
+            error!("failed to read the row count: {e}");
+            0
+        })
+    }
+
+    /// Returns the number of rows, or the SDK error if it cannot be read.
+    pub fn try_len(&self) -> Result<usize, RfcError> {
+        row_count(self.handle).map(|n| n as usize)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the rows that returns SDK errors instead of ending early.
+    pub fn try_iter(&self) -> SapTableTryIterator {
+        SapTableTryIterator::new(self.handle)
     }
 
     pub fn handle(&self) -> RFC_TABLE_HANDLE {
         self.handle
     }
 
-    pub fn add_row(&self, row: &serde_json::Value) -> Result<(), String> {
+    pub fn add_row(&self, row: &serde_json::Value) -> Result<(), RfcError> {
         if let serde_json::Value::Object(obj) = row {
             unsafe {
                 let mut errorInfo = error_info();
                 let type_handle = RfcDescribeType(self.handle, &mut errorInfo);
                 if errorInfo.code != 0 {
-                    return Err(String::from(&SapString::from(errorInfo.message.as_slice())));
+                    return Err(RfcError::from(errorInfo));
                 }
 
-                let structure = SapStructure::from_type(type_handle);
+                let structure = SapStructure::from_type(type_handle)?;
                 for (name, value) in obj {
                     match value {
-                        serde_json::Value::Null => todo!(),
-                        serde_json::Value::Bool(_v) => todo!(),
-                        serde_json::Value::Number(_number) => todo!(),
+                        serde_json::Value::Null => {}
+                        serde_json::Value::Bool(v) => structure.set(name, *v)?,
+                        serde_json::Value::Number(number) => {
+                            structure.set(name, number.to_string().as_str())?
+                        }
                         serde_json::Value::String(v) => structure.set(name, v.as_str())?,
-                        serde_json::Value::Array(_values) => todo!(),
-                        serde_json::Value::Object(_map) => todo!(),
+                        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                            return Err(RfcError::conversion(
+                                name,
+                                "nested values are not supported in table rows",
+                            ))
+                        }
                     }
                 }
-                RfcAppendRow(self.handle, structure.handle(), &mut errorInfo);
+                let rc = RfcAppendRow(self.handle, structure.handle(), &mut errorInfo);
+                if rc != 0 {
+                    return Err(RfcError::from(errorInfo));
+                }
             }
         }
         Ok(())
@@ -67,21 +91,41 @@ impl Serialize for SapTable {
     where
         S: serde::Serializer,
     {
-        let len = self.len();
+        self.serialize_with(serializer, BytesEncoding::default())
+    }
+}
+
+impl SerializeWith for SapTable {
+    fn serialize_with<S>(&self, serializer: S, encoding: BytesEncoding) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let len = row_count(self.handle).map_err(ser::Error::custom)?;
         trace!("Serializing SapTable with {} rows", len);
-        let mut tab = serializer.serialize_seq(Some(len))?;
-        for value in self.into_iter() {
-            tab.serialize_element(&value)?;
+        let mut tab = serializer.serialize_seq(Some(len as usize))?;
+        for idx in 0..len {
+            let row = current_row(self.handle, idx).map_err(ser::Error::custom)?;
+            tab.serialize_element(&row.with_bytes_encoding(encoding))?;
         }
         trace!("Serialized SapTable with {} rows", len);
         tab.end()
     }
 }
 
+/// Iterates over the rows of a table as `Value::Structure`.
+/// The iteration ends at the first row that cannot be read, the error is logged.
+/// Use [`SapTable::try_iter`] to get the error.
 pub struct SapTableIterator {
+    rows: SapTableTryIterator,
+}
+
+/// Iterates over the rows of a table as `Value::Structure`.
+/// An SDK error is returned as the last item.
+pub struct SapTableTryIterator {
     handle: RFC_TABLE_HANDLE,
     lines: u32,
     tabix: u32,
+    error: Option<RfcError>,
 }
 
 impl std::iter::IntoIterator for &SapTable {
@@ -98,11 +142,7 @@ impl std::fmt::Debug for SapTable {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut dbg = f.debug_struct("SapTable");
         dbg.field("handle", &self.handle);
-        unsafe {
-            let mut err_info = error_info();
-            let mut row_count: u32 = 0;
-            let rc = RfcGetRowCount(self.handle, &mut row_count, &mut err_info);
-            assert_eq!(0, rc);
+        if let Ok(row_count) = row_count(self.handle) {
             dbg.field("row-count", &Box::new(row_count) as &dyn std::fmt::Debug);
         }
         dbg.finish()
@@ -111,35 +151,59 @@ impl std::fmt::Debug for SapTable {
 
 impl SapTableIterator {
     pub fn new(handle: RFC_TABLE_HANDLE) -> Self {
-        let lines = unsafe {
-            let mut errorInfo = error_info();
-            let mut rowCount: u32 = 0;
-            let r = RfcGetRowCount(handle, &mut rowCount, &mut errorInfo);
-            assert_eq!(0, r);
-            rowCount
+        Self {
+            rows: SapTableTryIterator::new(handle),
+        }
+    }
+}
+
+impl Iterator for SapTableIterator {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next()? {
+            Ok(row) => Some(row),
+            Err(e) => {
+                error!("failed to read table row: {e}");
+                None
+            }
+        }
+    }
+}
+
+impl SapTableTryIterator {
+    pub fn new(handle: RFC_TABLE_HANDLE) -> Self {
+        let (lines, error) = match row_count(handle) {
+            Ok(lines) => (lines, None),
+            Err(e) => (0, Some(e)),
         };
         Self {
             handle,
             lines,
             tabix: 0,
+            error,
         }
     }
 }
 
-impl Iterator for SapTableIterator {
-    type Item = Value;
+impl Iterator for SapTableTryIterator {
+    type Item = Result<Value, RfcError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
         if self.tabix < self.lines {
-            let x = unsafe {
-                let mut errorInfo = error_info();
-                let rc = RfcMoveTo(self.handle, self.tabix, &mut errorInfo);
-                assert_eq!(0, rc);
-                let struct_handle = RfcGetCurrentRow(self.handle, &mut errorInfo);
-                self.tabix += 1;
-                SapStructure::new(struct_handle, true).unwrap()
-            };
-            Some(Value::Structure(x))
+            match current_row(self.handle, self.tabix) {
+                Ok(x) => {
+                    self.tabix += 1;
+                    Some(Ok(Value::Structure(x)))
+                }
+                Err(e) => {
+                    self.tabix = self.lines;
+                    Some(Err(e))
+                }
+            }
         } else {
             None
         }
@@ -156,3 +220,28 @@ impl Drop for SapTable {
         }
     }
 }
+
+/// Returns the number of rows of the table.
+fn row_count(handle: RFC_TABLE_HANDLE) -> Result<u32, RfcError> {
+    let mut errorInfo = error_info();
+    let mut rowCount: u32 = 0;
+    let rc = unsafe { RfcGetRowCount(handle, &mut rowCount, &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    Ok(rowCount)
+}
+
+/// Moves the table cursor to the given row and returns the row as a dependent structure.
+fn current_row(handle: RFC_TABLE_HANDLE, index: u32) -> Result<SapStructure, RfcError> {
+    let mut errorInfo = error_info();
+    let rc = unsafe { RfcMoveTo(handle, index, &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    let struct_handle = unsafe { RfcGetCurrentRow(handle, &mut errorInfo) };
+    if struct_handle.is_null() {
+        return Err(RfcError::from(errorInfo));
+    }
+    SapStructure::new(struct_handle, true)
+}
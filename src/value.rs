@@ -1,33 +1,191 @@
 use std::fmt::Display;
 
-use serde::ser::Serialize;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{NaiveDateTime, SecondsFormat};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{ser::Serialize, Serializer};
 
-use crate::{string::SapString, structure::SapStructure, table::SapTable};
+pub use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+pub use rust_decimal::Decimal;
+
+use crate::{error::RfcError, string::SapString, structure::SapStructure, table::SapTable};
+
+/// Text encoding of RAW and XSTRING values in JSON, YAML and other serialized formats.
+/// Values are serialized as base64 unless another encoding is chosen with
+/// [`SerializeWith::with_bytes_encoding`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BytesEncoding {
+    #[default]
+    Base64,
+    /// Two hexadecimal digits per byte, like the character representation in ABAP.
+    Hex,
+}
+
+impl BytesEncoding {
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        match self {
+            BytesEncoding::Base64 => STANDARD.encode(bytes),
+            BytesEncoding::Hex => bytes.iter().map(|b| format!("{b:02X}")).collect(),
+        }
+    }
+
+    /// Decodes a string, hexadecimal digits may be upper or lower case.
+    pub fn decode(&self, s: &str) -> Result<Vec<u8>, String> {
+        match self {
+            BytesEncoding::Base64 => STANDARD.decode(s).map_err(|e| e.to_string()),
+            BytesEncoding::Hex => {
+                if s.len() % 2 == 1 {
+                    return Err("odd number of hexadecimal digits".to_string());
+                }
+                (0..s.len())
+                    .step_by(2)
+                    .map(|i| {
+                        s.get(i..i + 2)
+                            .and_then(|x| u8::from_str_radix(x, 16).ok())
+                            .ok_or_else(|| format!("invalid hexadecimal digits at {i}"))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Serialization of values that contain bytes, with the encoding of the bytes chosen by
+/// the caller. The `Serialize` implementations of these types use base64.
+pub trait SerializeWith {
+    /// Serializes `self` with RAW and XSTRING values in the given encoding.
+    fn serialize_with<S>(&self, serializer: S, encoding: BytesEncoding) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer;
+
+    /// Returns a wrapper that serializes `self` with RAW and XSTRING values in the given
+    /// encoding, e.g. to pass it to `serde_json::to_writer`.
+    fn with_bytes_encoding(&self, encoding: BytesEncoding) -> WithBytesEncoding<'_, Self> {
+        WithBytesEncoding {
+            value: self,
+            encoding,
+        }
+    }
+}
+
+/// A value that is serialized with the given encoding of bytes,
+/// see [`SerializeWith::with_bytes_encoding`].
+pub struct WithBytesEncoding<'a, T: ?Sized> {
+    value: &'a T,
+    encoding: BytesEncoding,
+}
+
+impl<T: SerializeWith + ?Sized> Serialize for WithBytesEncoding<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize_with(serializer, self.encoding)
+    }
+}
+
+/// Decodes a string given for a RAW or XSTRING parameter or field.
+pub(crate) fn decode_bytes(
+    name: &str,
+    s: &str,
+    encoding: BytesEncoding,
+) -> Result<Vec<u8>, RfcError> {
+    encoding
+        .decode(s.trim())
+        .map_err(|e| RfcError::conversion(name, format!("not {encoding:?} encoded: {e}")))
+}
+
+/// Formats a time stamp in the character representation of an UTCLONG,
+/// `YYYY-MM-DDThh:mm:ss,fffffff` with 100 nanosecond precision.
+pub fn format_utclong(t: &DateTime<Utc>) -> String {
+    let fraction = (t.timestamp_subsec_nanos() / 100).min(9_999_999);
+    format!("{},{fraction:07}", t.format("%Y-%m-%dT%H:%M:%S"))
+}
+
+/// Parses the character representation of an UTCLONG, see [`format_utclong`].
+pub fn parse_utclong(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.replace(',', ".");
+    let s = s.trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|t| t.and_utc())
+}
 
 /// Represents a value in the SAP RFC protocol, which can be of various types.
-/// This enum can hold an empty value, a string, an integer, a table, or a structure.
-/// It implements serialization for use with Serde, allowing it to be easily
-/// converted to formats like JSON.
+/// Parameters and fields are read into the variant that matches their ABAP type:
+///
+/// | ABAP type                       | variant                        |
+/// |---------------------------------|--------------------------------|
+/// | CHAR, STRING, NUMC              | `String`                       |
+/// | INT1, INT2, INT4, INT8          | `Int`                          |
+/// | FLOAT                           | `Float`                        |
+/// | DEC (BCD), DECFLOAT16/34        | `Decimal`                      |
+/// | DATS                            | `Date`, `Empty` if initial     |
+/// | TIMS                            | `Time`                         |
+/// | UTCLONG                         | `Timestamp`, `Empty` if initial|
+/// | RAW, XSTRING                    | `Bytes`                        |
+/// | structures and tables           | `Structure`, `Table`           |
+/// | object references               | `Object`, `Empty` if initial   |
+///
+/// NUMC stays a string, so leading zeros like in message numbers are kept.
+/// Flags are ordinary CHAR1 fields in the metadata, so they are read as `String`;
+/// [`Value::as_bool`] interprets them and `Bool` can be used to set them.
+///
+/// Serialization preserves the type: integers and floats become numbers, decimals
+/// strings with all their digits, dates, times and time stamps ISO 8601 strings and
+/// bytes base64 or hexadecimal strings, see [`SerializeWith`].
+/// Objects are serialized as the JSON object they were read into.
 #[derive(Debug)]
 pub enum Value {
     Empty,
     String(SapString),
     Int(i64),
+    Float(f64),
+    /// A packed or decimal floating point number with all its digits.
+    /// It is serialized as a string, not as a number: JSON parsers read numbers as `f64`,
+    /// which would lose digits of amounts with more than 15 significant digits.
+    Decimal(Decimal),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Timestamp(DateTime<Utc>),
+    Bytes(Vec<u8>),
+    /// A flag, set as `X` or blank. Only used to set values; flags are read as `String`,
+    /// because the metadata does not tell them apart from other CHAR1 fields.
+    Bool(bool),
     Table(SapTable),
     Structure(SapStructure),
+    /// An ABAP object as JSON object with its `class` and its `attributes`.
+    /// The attributes are read when the object is read, bytes in them are base64 encoded.
+    Object(serde_json::Map<String, serde_json::Value>),
 }
 
 impl Serialize for Value {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: serde::Serializer,
+        S: Serializer,
+    {
+        self.serialize_with(serializer, BytesEncoding::default())
+    }
+}
+
+impl SerializeWith for Value {
+    fn serialize_with<S>(&self, serializer: S, encoding: BytesEncoding) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
     {
         match self {
             Value::Empty => serializer.serialize_unit(),
             Value::String(s) => serializer.serialize_str(&String::from(s)),
             Value::Int(i) => serializer.serialize_i64(*i),
-            Value::Table(t) => t.serialize(serializer),
-            Value::Structure(s) => s.serialize(serializer),
+            Value::Float(f) => serializer.serialize_f64(*f),
+            // a string, as JSON numbers are read as floats by most parsers
+            Value::Decimal(d) => serializer.collect_str(d),
+            Value::Date(_) | Value::Time(_) | Value::Timestamp(_) => serializer.collect_str(self),
+            Value::Bytes(b) => serializer.serialize_str(&encoding.encode(b)),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Table(t) => t.serialize_with(serializer, encoding),
+            Value::Structure(s) => s.serialize_with(serializer, encoding),
+            Value::Object(o) => o.serialize(serializer),
         }
     }
 }
@@ -37,9 +195,154 @@ impl Display for Value {
         match self {
             Value::String(s) => write!(f, "{}", String::from(s)),
             Value::Int(i) => write!(f, "{i}"),
-            Value::Table(_) => todo!(),
-            Value::Structure(_) => todo!(),
-            Value::Empty => todo!(),
+            Value::Float(x) => write!(f, "{x}"),
+            Value::Decimal(d) => write!(f, "{d}"),
+            Value::Date(d) => write!(f, "{}", d.format("%Y-%m-%d")),
+            Value::Time(t) => write!(f, "{}", t.format("%H:%M:%S")),
+            Value::Timestamp(t) => write!(f, "{}", t.to_rfc3339_opts(SecondsFormat::AutoSi, true)),
+            Value::Bytes(b) => b.iter().try_for_each(|x| write!(f, "{x:02X}")),
+            Value::Bool(b) => write!(f, "{}", if *b { "X" } else { "" }),
+            Value::Table(_) | Value::Structure(_) | Value::Object(_) => {
+                match serde_json::to_string(self) {
+                    Ok(s) => write!(f, "{s}"),
+                    Err(e) => write!(f, "<{} not readable: {e}>", self.kind()),
+                }
+            }
+            Value::Empty => Ok(()),
+        }
+    }
+}
+
+impl Value {
+    /// Returns the name of the variant, used in error messages.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Value::Empty => "empty value",
+            Value::String(_) => "string",
+            Value::Int(_) => "integer",
+            Value::Float(_) => "float",
+            Value::Decimal(_) => "decimal",
+            Value::Date(_) => "date",
+            Value::Time(_) => "time",
+            Value::Timestamp(_) => "timestamp",
+            Value::Bytes(_) => "bytes",
+            Value::Bool(_) => "flag",
+            Value::Table(_) => "table",
+            Value::Structure(_) => "structure",
+            Value::Object(_) => "object",
+        }
+    }
+
+    /// Returns `true` for `Empty`, i.e. an initial date or time stamp.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Value::Empty)
+    }
+
+    /// Returns the text of a `String`.
+    pub fn as_string(&self) -> Option<String> {
+        match self {
+            Value::String(s) => Some(String::from(s)),
+            _ => None,
+        }
+    }
+
+    /// Returns an integer, a decimal without fraction or a numeric string like NUMC as `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(i) => Some(*i),
+            Value::Decimal(d) if d.fract().is_zero() => d.to_i64(),
+            Value::String(s) => String::from(s).trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns a number as `f64`, decimals are rounded to the nearest float.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            Value::Decimal(d) => d.to_f64(),
+            _ => None,
+        }
+    }
+
+    /// Returns a number as exact `Decimal`.
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self {
+            Value::Int(i) => Some(Decimal::from(*i)),
+            Value::Float(f) => Decimal::from_f64(*f),
+            Value::Decimal(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    pub fn as_date(&self) -> Option<NaiveDate> {
+        match self {
+            Value::Date(d) => Some(*d),
+            _ => None,
+        }
+    }
+
+    pub fn as_time(&self) -> Option<NaiveTime> {
+        match self {
+            Value::Time(t) => Some(*t),
+            _ => None,
+        }
+    }
+
+    /// Returns a `Timestamp`, or a packed time stamp like `TIMESTAMP` or `TIMESTAMPL`
+    /// in the form `YYYYMMDDhhmmss.fffffff` as time in UTC.
+    pub fn as_timestamp(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Value::Timestamp(t) => Some(*t),
+            Value::Decimal(d) => {
+                let seconds = d.trunc().to_i64()?;
+                let nanos = (d.fract() * Decimal::from(1_000_000_000)).to_u32()?;
+                let t = NaiveDateTime::parse_from_str(&seconds.to_string(), "%Y%m%d%H%M%S").ok()?;
+                Some(t.and_utc() + chrono::Duration::nanoseconds(nanos as i64))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns a `Bool`, or interprets an ABAP flag: `X` is `true`, blank is `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::String(s) => match String::from(s).trim() {
+                "X" => Some(true),
+                "" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn as_structure(&self) -> Option<&SapStructure> {
+        match self {
+            Value::Structure(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_table(&self) -> Option<&SapTable> {
+        match self {
+            Value::Table(t) => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        match self {
+            Value::Object(o) => Some(o),
+            _ => None,
         }
     }
 }
@@ -49,3 +352,87 @@ impl From<&str> for Value {
         Value::String(SapString::from(value))
     }
 }
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(SapString::from(value))
+    }
+}
+
+macro_rules! from_int {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Value {
+            fn from(value: $t) -> Self {
+                Value::Int(value as i64)
+            }
+        })*
+    };
+}
+
+from_int!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::Float(value as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<Decimal> for Value {
+    fn from(value: Decimal) -> Self {
+        Value::Decimal(value)
+    }
+}
+
+impl From<NaiveDate> for Value {
+    fn from(value: NaiveDate) -> Self {
+        Value::Date(value)
+    }
+}
+
+impl From<NaiveTime> for Value {
+    fn from(value: NaiveTime) -> Self {
+        Value::Time(value)
+    }
+}
+
+impl From<DateTime<Utc>> for Value {
+    fn from(value: DateTime<Utc>) -> Self {
+        Value::Timestamp(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    fn from(value: Vec<u8>) -> Self {
+        Value::Bytes(value)
+    }
+}
+
+impl From<&[u8]> for Value {
+    fn from(value: &[u8]) -> Self {
+        Value::Bytes(value.to_vec())
+    }
+}
+
+impl From<SapStructure> for Value {
+    fn from(value: SapStructure) -> Self {
+        Value::Structure(value)
+    }
+}
+
+impl From<SapTable> for Value {
+    fn from(value: SapTable) -> Self {
+        Value::Table(value)
+    }
+}
@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::RfcError,
+    error_info,
+    librfc::{RfcSetWebsocketPingInterval, RfcSetWebsocketPongTimeout},
+    rfc_param::RfcParam,
+    sdk::seconds,
+    secret::Secret,
+};
+
+/// Settings of a WebSocket RFC connection, used to reach systems like S/4HANA Cloud
+/// over HTTPS instead of the classic gateway.
+/// TLS is always used. The client PSE contains the trusted server certificates and,
+/// for a logon with a client certificate, the own certificate.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WebsocketConfig {
+    host: String,
+    port: u16,
+    client_pse: Option<String>,
+    sapcryptolib: Option<String>,
+    trust_all: Option<bool>,
+    client_certificate_logon: Option<bool>,
+    proxy_host: Option<String>,
+    proxy_port: Option<u16>,
+    proxy_user: Option<String>,
+    proxy_passwd: Option<Secret>,
+}
+
+impl WebsocketConfig {
+    /// Creates a WebSocket configuration for the given host and HTTPS port.
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            client_pse: None,
+            sapcryptolib: None,
+            trust_all: None,
+            client_certificate_logon: None,
+            proxy_host: None,
+            proxy_port: None,
+            proxy_user: None,
+            proxy_passwd: None,
+        }
+    }
+
+    /// Sets the PSE file with the trusted certificates and the own certificate
+    /// (`tls_client_pse`).
+    pub fn client_pse(mut self, path: &str) -> Self {
+        self.client_pse = Some(path.to_string());
+        self
+    }
+
+    /// Sets the path of the SAP Cryptographic Library (`tls_sapcryptolib`).
+    pub fn sapcryptolib(mut self, path: &str) -> Self {
+        self.sapcryptolib = Some(path.to_string());
+        self
+    }
+
+    /// Accepts any server certificate without verification (`tls_trust_all`).
+    /// Only use this for tests.
+    pub fn trust_all(mut self, trust_all: bool) -> Self {
+        self.trust_all = Some(trust_all);
+        self
+    }
+
+    /// Logs on with the client certificate of the PSE instead of user and password
+    /// (`tls_client_certificate_logon`).
+    pub fn client_certificate_logon(mut self, enabled: bool) -> Self {
+        self.client_certificate_logon = Some(enabled);
+        self
+    }
+
+    /// Connects through an HTTP proxy (`proxy_host`, `proxy_port`).
+    pub fn proxy(mut self, host: &str, port: u16) -> Self {
+        self.proxy_host = Some(host.to_string());
+        self.proxy_port = Some(port);
+        self
+    }
+
+    /// Sets the user and password for the HTTP proxy (`proxy_user`, `proxy_passwd`).
+    pub fn proxy_credentials<S: Into<Secret>>(mut self, user: &str, passwd: S) -> Self {
+        self.proxy_user = Some(user.to_string());
+        self.proxy_passwd = Some(passwd.into());
+        self
+    }
+
+    /// Returns the connection parameters for this configuration.
+    pub fn to_params(&self) -> Vec<RfcParam> {
+        let flag = |b: bool| if b { "1" } else { "0" };
+        let mut v = vec![
+            RfcParam::new("wshost", self.host.as_str()),
+            RfcParam::new("wsport", self.port.to_string().as_str()),
+        ];
+        if let Some(pse) = &self.client_pse {
+            v.push(RfcParam::new("tls_client_pse", pse.as_str()));
+        }
+        if let Some(lib) = &self.sapcryptolib {
+            v.push(RfcParam::new("tls_sapcryptolib", lib.as_str()));
+        }
+        if let Some(trust_all) = self.trust_all {
+            v.push(RfcParam::new("tls_trust_all", flag(trust_all)));
+        }
+        if let Some(logon) = self.client_certificate_logon {
+            v.push(RfcParam::new("tls_client_certificate_logon", flag(logon)));
+        }
+        if let Some(host) = &self.proxy_host {
+            v.push(RfcParam::new("proxy_host", host.as_str()));
+        }
+        if let Some(port) = self.proxy_port {
+            v.push(RfcParam::new("proxy_port", port.to_string().as_str()));
+        }
+        if let Some(user) = &self.proxy_user {
+            v.push(RfcParam::new("proxy_user", user.as_str()));
+        }
+        if let Some(passwd) = &self.proxy_passwd {
+            v.push(RfcParam::secret("proxy_passwd", passwd));
+        }
+        v
+    }
+}
+
+/// Sets the idle time after which a keep alive ping is sent on all WebSocket connections
+/// of the process, like `RFC_WEBSOCKET_PING_INTERVAL` in the `sapnwrfc.ini` file.
+/// # Arguments
+/// * `interval` - Zero to turn the ping off, or between 10 seconds and one day.
+///   The SDK default is 300 seconds.
+/// # Errors
+/// * Returns the SDK error if the interval is out of range.
+pub fn set_ping_interval(interval: Duration) -> Result<(), RfcError> {
+    let mut errorInfo = error_info();
+    let rc = unsafe { RfcSetWebsocketPingInterval(seconds(interval), &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    Ok(())
+}
+
+/// Sets the time to wait for the reply to a keep alive ping on all WebSocket connections
+/// of the process, like `RFC_WEBSOCKET_PONG_TIMEOUT` in the `sapnwrfc.ini` file.
+/// A connection without reply is considered broken and closed.
+/// # Arguments
+/// * `timeout` - Zero to turn the check off, or between 10 seconds and one hour.
+///   The SDK default is 60 seconds; keep it below the ping interval.
+/// # Errors
+/// * Returns the SDK error if the timeout is out of range.
+pub fn set_pong_timeout(timeout: Duration) -> Result<(), RfcError> {
+    let mut errorInfo = error_info();
+    let rc = unsafe { RfcSetWebsocketPongTimeout(seconds(timeout), &mut errorInfo) };
+    if rc != 0 {
+        return Err(RfcError::from(errorInfo));
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "async")]
+
+use std::time::Duration;
+
+use librfc_rust::{
+    async_connection::AsyncConnection, connection::Connection, error::RfcReturnCode, RfcError,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn call() {
+    let c = AsyncConnection::connect(Connection::new().destination("sap"))
+        .await
+        .unwrap();
+    let result = c
+        .call("RFC_SYSTEM_INFO", serde_json::json!({}))
+        .await
+        .unwrap();
+    assert!(result.get("RFCSI_EXPORT").is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timeout_cancels_call() {
+    let c = AsyncConnection::connect(Connection::new().destination("sap"))
+        .await
+        .unwrap();
+    // RFC_PING_AND_WAIT blocks on the server for the given number of seconds
+    let start = std::time::Instant::now();
+    let err = c
+        .call_with_timeout(
+            "RFC_PING_AND_WAIT",
+            serde_json::json!({"SECONDS": 10}),
+            Duration::from_millis(500),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, RfcError::TimedOut(d) if d == Duration::from_millis(500)));
+    assert_eq!(err.code(), Some(RfcReturnCode::Timeout));
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(500) && elapsed < Duration::from_secs(5));
+    // the cancelled connection is reopened by the next call
+    assert!(c.call("RFC_PING", serde_json::json!({})).await.is_ok());
+}
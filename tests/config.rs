@@ -0,0 +1,72 @@
+use librfc_rust::config::Profiles;
+
+const PROFILES: &str = r#"
+defaults:
+  client: "100"
+  lang: EN
+systems:
+  dev:
+    ashost: sapdev
+    sysnr: "00"
+    user: ${CONFIG_TEST_USER}
+    passwd: secret
+  qas:
+    extends: dev
+    ashost: sapqas
+    client: "200"
+"#;
+
+#[test]
+fn inherit_from_parent_and_defaults() {
+    std::env::set_var("CONFIG_TEST_USER", "DEVELOPER");
+    let profiles = Profiles::from_yaml(PROFILES).unwrap();
+    let qas = profiles.get("qas").unwrap();
+    assert_eq!(qas.ashost.as_deref(), Some("sapqas"));
+    assert_eq!(qas.sysnr.as_deref(), Some("00"));
+    assert_eq!(qas.client.as_deref(), Some("200"));
+    assert_eq!(qas.lang.as_deref(), Some("EN"));
+    assert_eq!(qas.user.as_deref(), Some("DEVELOPER"));
+    assert!(qas.connection().unwrap().validate().is_ok());
+}
+
+#[test]
+fn unknown_profile_and_cycles() {
+    let profiles = Profiles::from_toml(
+        r#"
+        [systems.a]
+        extends = "b"
+        [systems.b]
+        extends = "a"
+        "#,
+    )
+    .unwrap();
+    assert!(profiles.get("a").is_err());
+    assert!(profiles.get("c").is_err());
+}
+
+#[test]
+fn interpolation_errors_hide_passwords() {
+    let profiles = Profiles::from_yaml(
+        r#"
+systems:
+  dev:
+    ashost: sapdev
+    passwd: "pa$${ss"
+  broken:
+    ashost: sapdev
+    passwd: "top${secret"
+"#,
+    )
+    .unwrap();
+    let dev = profiles.get("dev").unwrap();
+    let passwd = dev
+        .to_params()
+        .unwrap()
+        .into_iter()
+        .find(|p| p.is_secret())
+        .unwrap();
+    assert_eq!(String::from(passwd.value()), "pa${ss");
+    let e = profiles.get("broken").unwrap_err().to_string();
+    assert!(e.contains("passwd"));
+    assert!(!e.contains("top"));
+}
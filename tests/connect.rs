@@ -1,4 +1,16 @@
-use librfc_rust::{connection::Connection, value::Value};
+use std::time::Duration;
+
+use librfc_rust::{
+    connection::Connection,
+    credentials::Credentials,
+    error::{AbapClassException, RfcErrorGroup, RfcReturnCode},
+    rfc_param::RfcParam,
+    secret::Secret,
+    snc::{SncConfig, SncQop},
+    value::{NaiveDate, Value},
+    websocket::WebsocketConfig,
+    RfcError,
+};
 
 // struct Sample {
 //     x: usize,
@@ -44,6 +56,52 @@ fn ping() {
     println!("Struct: {:#?}", f.get("RFCSI_EXPORT"));
 }
 
+#[test]
+fn health_check() {
+    let mut c = Connection::new().destination("sap").connect().unwrap();
+    assert!(c.is_alive());
+    c.ping().unwrap();
+    c.reopen().unwrap();
+    assert!(c.is_alive());
+    c.ping().unwrap();
+    assert!(!Connection::new().is_alive());
+}
+
+#[test]
+fn connection_attributes() {
+    let c = Connection::new().destination("sap").connect().unwrap();
+    let attributes = c.attributes().unwrap();
+    assert!(!attributes.sys_id.is_empty());
+    assert!(!attributes.client.is_empty());
+    assert_eq!(attributes.rfc_role, "C");
+    println!("{}", serde_json::to_string(&attributes).unwrap());
+}
+
+#[test]
+fn execute_with_timeout() {
+    let mut c = Connection::new().destination("sap").connect().unwrap();
+    // RFC_PING_AND_WAIT blocks on the server for the given number of seconds
+    let f = c.function("RFC_PING_AND_WAIT").unwrap();
+    f.set("SECONDS", "10").unwrap();
+    let start = std::time::Instant::now();
+    let err = f
+        .execute_with_timeout(Duration::from_millis(500))
+        .unwrap_err();
+    assert!(matches!(err, RfcError::TimedOut(_)));
+    assert!(start.elapsed() < Duration::from_secs(5));
+    drop(f);
+
+    c.reopen().unwrap();
+    let f = c.function("RFC_PING").unwrap();
+    f.execute_with_timeout(Duration::from_secs(5)).unwrap();
+    drop(f);
+
+    // a closed connection cannot be cancelled
+    let cancel = c.cancel_handle();
+    drop(c);
+    assert!(cancel.cancel().is_err());
+}
+
 #[test]
 fn search_function() {
     let c = Connection::new().destination("sap").connect().unwrap();
@@ -60,3 +118,201 @@ fn search_function() {
         }
     }
 }
+
+#[test]
+fn function_exceptions() {
+    let c = Connection::new().destination("sap").connect().unwrap();
+    let f = c.function("RFC_READ_TABLE").unwrap();
+    assert!(f.has_exception("TABLE_NOT_AVAILABLE"));
+    f.set("QUERY_TABLE", "ZZ_DOES_NOT_EXIST").unwrap();
+    let e = f.try_execute().unwrap().expect("exception expected");
+    assert!(e.is("TABLE_NOT_AVAILABLE"));
+}
+
+#[test]
+fn class_exception_error() {
+    let attributes = serde_json::json!({
+        "TEXTID": { "MSGID": "SABAPRC", "MSGNO": 1 },
+        "PREVIOUS": null,
+        "OPERATION": "/",
+    });
+    let e = RfcError::AbapClassException(AbapClassException {
+        class_name: "CX_SY_ZERODIVIDE".to_string(),
+        message: "Division by zero".to_string(),
+        attributes: attributes.as_object().unwrap().clone(),
+    });
+    assert_eq!(e.code(), Some(RfcReturnCode::AbapClassException));
+    assert_eq!(e.group(), Some(RfcErrorGroup::AbapApplicationFailure));
+    assert!(e.abap_exception().is_none());
+    assert!(e.to_string().contains("CX_SY_ZERODIVIDE"));
+    let exception = e.abap_class_exception().unwrap();
+    assert_eq!(
+        serde_json::to_value(exception).unwrap(),
+        serde_json::json!({
+            "class_name": "CX_SY_ZERODIVIDE",
+            "message": "Division by zero",
+            "attributes": attributes,
+        })
+    );
+}
+
+#[test]
+fn function_with_class_exceptions() {
+    let c = Connection::new().destination("sap").connect().unwrap();
+    let f = c.function_with_class_exceptions("STFC_CONNECTION").unwrap();
+    f.set("REQUTEXT", "class exceptions").unwrap();
+    f.execute().unwrap();
+    let echo = f.get("ECHOTEXT").unwrap().as_string().unwrap();
+    assert!(echo.starts_with("class exceptions"));
+}
+
+#[test]
+fn validate_parameters() {
+    let c = Connection::new().ashost("sapserver").client("100");
+    assert!(matches!(c.validate(), Err(RfcError::Config(_))));
+    let c = c.sysnr("00").user("DEVELOPER").passwd("secret").lang("EN");
+    assert!(c.validate().is_ok());
+    let c = Connection::new().ashost("sapserver").mshost("sapms");
+    assert!(c.validate().is_err());
+}
+
+#[test]
+fn validate_snc_parameters() {
+    let c = Connection::new()
+        .ashost("sapserver")
+        .sysnr("00")
+        .client("100")
+        .x509cert("MIIC...");
+    assert!(matches!(c.validate(), Err(RfcError::Config(_))));
+    let c = c.snc(
+        SncConfig::new("p:CN=SAP, O=Example, C=DE")
+            .qop(SncQop::Privacy)
+            .lib("/usr/sap/lib/libsapcrypto.so"),
+    );
+    assert!(c.validate().is_ok());
+}
+
+#[test]
+fn validate_credentials() {
+    let c = Connection::new()
+        .ashost("sapserver")
+        .sysnr("00")
+        .client("100")
+        .user("DEVELOPER");
+    assert!(matches!(c.validate(), Err(RfcError::Config(_))));
+    let c = c.credentials(Credentials::SsoTicket(Secret::new("AjQxMDMBAB...")));
+    assert!(c.validate().is_ok());
+    let c = c.credentials(Credentials::Snc);
+    assert!(matches!(c.validate(), Err(RfcError::Config(_))));
+    let c = c.snc(SncConfig::new("p:CN=SAP, O=Example, C=DE"));
+    assert!(c.validate().is_ok());
+    let c = c.user("DEVELOPER");
+    assert!(c.validate().is_ok());
+    let c = c.snc(SncConfig::new("p:CN=SAP, O=Example, C=DE").sso(false));
+    assert!(matches!(c.validate(), Err(RfcError::Config(_))));
+    let c = c.credentials(Credentials::Snc);
+    let c = c.credentials(Credentials::password("DEVELOPER", "secret"));
+    assert!(c.validate().is_ok());
+    assert!(!c.get_params().iter().any(|p| String::from(p) == "snc_sso"));
+}
+
+#[test]
+fn validate_websocket_parameters() {
+    let c = Connection::new()
+        .client("100")
+        .user("DEVELOPER")
+        .passwd("secret")
+        .param(RfcParam::new("wshost", "my.s4hana.ondemand.com"));
+    assert!(matches!(c.validate(), Err(RfcError::Config(_))));
+    let c = c.websocket(
+        WebsocketConfig::new("my.s4hana.ondemand.com", 443).client_pse("/usr/sap/sec/client.pse"),
+    );
+    assert!(c.validate().is_ok());
+    assert!(c.ashost("sapserver").sysnr("00").validate().is_err());
+}
+
+#[test]
+fn typed_setters() {
+    let c = Connection::new().destination("sap").connect().unwrap();
+    let f = c.function("STFC_STRUCTURE").unwrap();
+    let Value::Structure(s) = f.get("IMPORTSTRUCT").unwrap() else {
+        panic!("structure expected");
+    };
+    s.set("RFCFLOAT", 1.5).unwrap();
+    s.set("RFCINT1", 255).unwrap();
+    s.set("RFCINT2", -300).unwrap();
+    s.set("RFCINT4", "42").unwrap();
+    s.set("RFCDATE", "2024-02-29").unwrap();
+    s.set("RFCTIME", "12:30:00").unwrap();
+    s.set("RFCHEX3", vec![0xde, 0xad, 0xbe]).unwrap();
+    s.set("RFCCHAR4", "ABCD").unwrap();
+    assert!(matches!(
+        s.set("RFCINT1", 256),
+        Err(RfcError::Conversion { .. })
+    ));
+    assert!(matches!(
+        s.set("RFCDATE", "29.02.2024"),
+        Err(RfcError::Conversion { .. })
+    ));
+    assert!(matches!(
+        s.set("RFCHEX3", vec![0; 4]),
+        Err(RfcError::Conversion { .. })
+    ));
+    f.execute().unwrap();
+}
+
+#[test]
+fn typed_getters() {
+    let c = Connection::new().destination("sap").connect().unwrap();
+    let f = c.function("STFC_STRUCTURE").unwrap();
+    let Value::Structure(s) = f.get("IMPORTSTRUCT").unwrap() else {
+        panic!("structure expected");
+    };
+    s.set("RFCFLOAT", 1.5).unwrap();
+    s.set("RFCINT2", -300).unwrap();
+    s.set("RFCDATE", "20240229").unwrap();
+    s.set("RFCHEX3", vec![0xde, 0xad, 0xbe]).unwrap();
+    f.execute().unwrap();
+    let Value::Structure(echo) = f.get("ECHOSTRUCT").unwrap() else {
+        panic!("structure expected");
+    };
+    assert!(matches!(echo.get("RFCFLOAT").unwrap(), Value::Float(x) if x == 1.5));
+    assert!(matches!(echo.get("RFCINT2").unwrap(), Value::Int(-300)));
+    assert_eq!(
+        echo.get("RFCDATE").unwrap().as_date(),
+        NaiveDate::from_ymd_opt(2024, 2, 29)
+    );
+    assert!(matches!(echo.get("RFCHEX3").unwrap(), Value::Bytes(b) if b == [0xde, 0xad, 0xbe]));
+    assert!(matches!(f.get("RESPTEXT").unwrap(), Value::String(_)));
+}
+
+#[test]
+fn typed_json_parameters() {
+    let c = Connection::new().destination("sap").connect().unwrap();
+    let f = c.function("STFC_STRUCTURE").unwrap();
+    f.set_parameters(&serde_json::json!({
+        "IMPORTSTRUCT": {
+            "RFCFLOAT": 1.5,
+            "RFCINT1": 7,
+            "RFCINT4": "42",
+            "RFCDATE": "2024-02-29",
+            "RFCTIME": "12:30:00",
+            "RFCHEX3": "3q2+",
+            "RFCCHAR4": null
+        }
+    }))
+    .unwrap();
+    f.execute().unwrap();
+    let Value::Structure(echo) = f.get("ECHOSTRUCT").unwrap() else {
+        panic!("structure expected");
+    };
+    assert!(matches!(echo.get("RFCINT4").unwrap(), Value::Int(42)));
+    assert!(matches!(echo.get("RFCHEX3").unwrap(), Value::Bytes(b) if b == [0xde, 0xad, 0xbe]));
+
+    let f = c.function("RFC_PING_AND_WAIT").unwrap();
+    f.set_parameters(&serde_json::json!({ "SECONDS": 1 }))
+        .unwrap();
+    assert!(f
+        .set_parameters(&serde_json::json!({ "SECONDS": "one" }))
+        .is_err());
+}
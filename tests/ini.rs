@@ -0,0 +1,51 @@
+use librfc_rust::{ini::IniFile, RfcError};
+
+const INI: &str = r#"
+DEFAULT
+RFC_TRACE=0
+
+# development system
+DEST=DEV
+ASHOST=sapdev.example.com
+SYSNR=00
+CLIENT=100
+USER=developer
+PASSWD=secret
+
+DEST=QAS
+MSHOST=sapqas.example.com
+CLIENT=200
+USER=tester
+PASSWD=secret
+"#;
+
+#[test]
+fn parse_and_mask() -> Result<(), RfcError> {
+    let ini = IniFile::parse(INI)?;
+    assert_eq!(ini.names().collect::<Vec<_>>(), vec!["DEV", "QAS"]);
+    assert_eq!(ini.defaults.get("RFC_TRACE").map(|s| s.as_str()), Some("0"));
+
+    let dev = ini.destination("dev")?;
+    assert_eq!(dev.param("ashost"), Some("sapdev.example.com"));
+    assert_eq!(dev.masked().param("passwd"), Some("********"));
+    assert_eq!(dev.masked().param("user"), Some("developer"));
+    assert!(dev.validate().is_ok());
+
+    // QAS has neither msserv nor sysid
+    let qas = ini.destination("QAS")?;
+    assert!(matches!(qas.validate(), Err(RfcError::Config(_))));
+    Ok(())
+}
+
+#[test]
+fn suggest_destination() -> Result<(), RfcError> {
+    let ini = IniFile::parse(INI)?;
+    assert_eq!(ini.suggest("DVE"), Some("DEV"));
+    assert_eq!(ini.suggest("PRODUCTION"), None);
+    let err = ini.destination("QSA").unwrap_err();
+    assert!(err.to_string().contains("did you mean 'QAS'"));
+
+    assert!(IniFile::parse("DEST=A\nDEST=a\n").is_err());
+    assert!(IniFile::parse("DEST=A\nASHOST\n").is_err());
+    Ok(())
+}
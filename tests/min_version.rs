@@ -0,0 +1,23 @@
+// The minimum version is a process-wide setting, so it is tested in its own test binary.
+use librfc_rust::{
+    connection::Connection,
+    sdk::{SdkConfig, VersionPolicy},
+    RfcError,
+};
+
+#[test]
+fn min_version() {
+    let version = librfc_rust::sdk_version();
+    assert!(SdkConfig::default()
+        .min_version(version.major + 1, 0, 0, VersionPolicy::Refuse)
+        .apply()
+        .is_err());
+    let result = Connection::new().destination("sap").connect();
+    assert!(matches!(result, Err(RfcError::Config(_))));
+
+    assert!(SdkConfig::default()
+        .min_version(version.major + 1, 0, 0, VersionPolicy::Warn)
+        .apply()
+        .is_ok());
+    assert!(Connection::new().destination("sap").connect().is_ok());
+}
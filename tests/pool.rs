@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use librfc_rust::pool::{ConnectionPool, PoolConfig, PoolRegistry};
+
+#[test]
+fn reuse_connection() {
+    let pool = ConnectionPool::for_destination("sap", PoolConfig::default().max_size(1));
+    {
+        let c = pool.get().unwrap();
+        let f = c.function("RFC_PING").unwrap();
+        f.execute().unwrap();
+        assert_eq!(pool.status().unwrap().idle, 0);
+    }
+    let status = pool.status().unwrap();
+    assert_eq!(status.open, 1);
+    assert_eq!(status.idle, 1);
+    let _c = pool.get().unwrap();
+    assert_eq!(pool.status().unwrap().open, 1);
+}
+
+#[test]
+fn checkout_timeout() {
+    let pool = ConnectionPool::for_destination(
+        "sap",
+        PoolConfig::default()
+            .max_size(1)
+            .checkout_timeout(Duration::from_millis(100)),
+    );
+    let c = pool.get().unwrap();
+    let handle = {
+        let pool = pool.clone();
+        std::thread::spawn(move || pool.get().is_err())
+    };
+    assert!(handle.join().unwrap());
+    drop(c);
+    assert!(pool.get().is_ok());
+}
+
+#[test]
+fn min_idle() {
+    let registry = PoolRegistry::new(PoolConfig::default().min_idle(2));
+    let pool = registry.destination("sap").unwrap();
+    pool.maintain().unwrap();
+    assert_eq!(pool.status().unwrap().idle, 2);
+    assert_eq!(
+        registry.destination("sap").unwrap().status().unwrap().idle,
+        2
+    );
+}
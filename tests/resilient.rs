@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use librfc_rust::{
+    connection::Connection,
+    error::{AbapException, ErrorClass, RfcErrorGroup, RfcErrorInfo, RfcReturnCode},
+    resilient::{ResilientConnection, RetryPolicy},
+    RfcError,
+};
+
+#[test]
+fn backoff() {
+    let policy = RetryPolicy::default()
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(500))
+        .multiplier(3.0);
+    assert_eq!(policy.delay(0), Duration::from_millis(100));
+    assert_eq!(policy.delay(1), Duration::from_millis(300));
+    assert_eq!(policy.delay(2), Duration::from_millis(500));
+    assert_eq!(policy.delay(u32::MAX), Duration::from_millis(500));
+}
+
+#[test]
+fn error_classes() {
+    assert_eq!(RfcError::Config("x".into()).class(), ErrorClass::Other);
+    let e = RfcError::AbapException(AbapException {
+        key: "NOT_FOUND".into(),
+        message: String::new(),
+        msg_class: String::new(),
+        msg_type: String::new(),
+        msg_number: String::new(),
+        vars: Default::default(),
+    });
+    assert_eq!(e.class(), ErrorClass::Application);
+}
+
+fn error_info(code: RfcReturnCode, group: RfcErrorGroup) -> RfcErrorInfo {
+    RfcErrorInfo {
+        code,
+        group,
+        key: String::new(),
+        message: String::new(),
+        abap_msg_class: String::new(),
+        abap_msg_type: String::new(),
+        abap_msg_number: String::new(),
+        abap_msg_v1: String::new(),
+        abap_msg_v2: String::new(),
+        abap_msg_v3: String::new(),
+        abap_msg_v4: String::new(),
+    }
+}
+
+#[test]
+fn communication_failures() {
+    let closed = RfcError::Sdk(error_info(
+        RfcReturnCode::Closed,
+        RfcErrorGroup::ExternalRuntimeFailure,
+    ));
+    assert_eq!(closed.class(), ErrorClass::Communication);
+    assert!(closed.is_communication_failure());
+
+    let info = error_info(RfcReturnCode::Canceled, RfcErrorGroup::CommunicationFailure);
+    let cancelled = RfcError::Cancelled(Some(info.clone()));
+    assert_eq!(cancelled.info(), Some(&info));
+    assert_eq!(cancelled.code(), Some(RfcReturnCode::Canceled));
+    assert_eq!(cancelled.class(), ErrorClass::Other);
+    assert!(!cancelled.is_communication_failure());
+}
+
+#[test]
+fn idempotent_call() {
+    let mut c = ResilientConnection::new(Connection::new().destination("sap"))
+        .policy(RetryPolicy::default().max_retries(1));
+    let info = c
+        .call_idempotent(
+            "RFC_SYSTEM_INFO",
+            |_| Ok(()),
+            |f| f.get("RFCSI_EXPORT").map(|v| v.to_string()),
+        )
+        .unwrap();
+    assert!(!info.is_empty());
+    assert!(c.connection().is_connected());
+}
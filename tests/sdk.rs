@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use librfc_rust::{
+    connection::Connection,
+    sdk::{SdkConfig, TraceEncoding},
+};
+
+#[test]
+fn apply_sdk_config() {
+    let dir = std::env::temp_dir();
+    SdkConfig::default()
+        .trace_level(1)
+        .trace_dir(&dir)
+        .trace_encoding(TraceEncoding::Utf8)
+        .logon_timeout(Duration::from_secs(30))
+        .apply()
+        .unwrap();
+    let c = Connection::new().destination("sap").connect().unwrap();
+    c.ping().unwrap();
+    SdkConfig::default().trace_level(0).apply().unwrap();
+
+    let missing = dir.join("sdk-test-does-not-exist");
+    assert!(SdkConfig::default().trace_dir(missing).apply().is_err());
+}
+
+#[test]
+fn sdk_version() {
+    let version = librfc_rust::sdk_version();
+    println!("SAP NW RFC SDK {version} ({})", version.version);
+    assert!(version.at_least(7500, 0, 0));
+    assert!(!version.at_least(version.major + 1, 0, 0));
+}
@@ -0,0 +1,70 @@
+use librfc_rust::{
+    config::Profiles,
+    rfc_param::RfcParam,
+    secret::{Secret, SecretSource},
+};
+
+#[test]
+fn secrets_are_masked() {
+    let secret = Secret::new("geheim");
+    assert_eq!(format!("{secret:?}"), "Secret(********)");
+    assert_eq!(serde_json::to_string(&secret).unwrap(), r#""********""#);
+    let param = RfcParam::secret("passwd", &secret);
+    assert!(param.is_secret());
+    assert!(!format!("{param:?}").contains("geheim"));
+    assert!(RfcParam::new("PASSWD", "geheim").is_secret());
+    assert!(!RfcParam::new("user", "geheim").is_secret());
+    // a certificate is public, only its private key is confidential
+    assert!(!RfcParam::new("x509cert", "MIIC...").is_secret());
+}
+
+#[test]
+fn secret_from_env_and_file() {
+    std::env::set_var("SECRET_TEST_PASSWORD", "from-env");
+    assert_eq!(
+        Secret::from_env("SECRET_TEST_PASSWORD").unwrap().expose(),
+        "from-env"
+    );
+    assert!(Secret::from_env("SECRET_TEST_UNSET").is_err());
+
+    let path = std::env::temp_dir().join(format!("secret-test-{}", std::process::id()));
+    std::fs::write(&path, "from-file\n").unwrap();
+    let secret = Secret::from_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(secret.unwrap().expose(), "from-file");
+}
+
+#[cfg(unix)]
+#[test]
+fn secret_from_command() {
+    assert_eq!(
+        Secret::from_command(&["echo", "from-command"])
+            .unwrap()
+            .expose(),
+        "from-command"
+    );
+    assert!(Secret::from_command(&["false"]).is_err());
+}
+
+#[test]
+fn password_source_in_profile() {
+    std::env::set_var("SECRET_TEST_PROFILE_PASSWORD", "from-profile");
+    let profiles = Profiles::from_yaml(
+        r#"
+systems:
+  dev:
+    ashost: sapdev
+    sysnr: "00"
+    client: "100"
+    user: DEVELOPER
+    passwd: { env: SECRET_TEST_PROFILE_PASSWORD }
+"#,
+    )
+    .unwrap();
+    let dev = profiles.get("dev").unwrap();
+    match dev.passwd.as_ref().map(SecretSource::resolve) {
+        Some(Ok(secret)) => assert_eq!(secret.expose(), "from-profile"),
+        other => panic!("unexpected password {other:?}"),
+    }
+    assert!(dev.connection().unwrap().validate().is_ok());
+}
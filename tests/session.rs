@@ -0,0 +1,28 @@
+use librfc_rust::{connection::Connection, error::ErrorClass, RfcError};
+
+#[test]
+fn session_resets_context() {
+    let mut c = Connection::new().destination("sap").connect().unwrap();
+    {
+        let mut session = c.session();
+        session.function("RFC_PING").unwrap().execute().unwrap();
+        session.rollback().unwrap();
+        session.reset().unwrap();
+        session.commit(true).unwrap();
+    }
+    assert!(c.is_alive());
+    c.reset_server_context().unwrap();
+}
+
+#[test]
+fn bapi_return_error() {
+    let e = RfcError::BapiReturn {
+        msg_type: "E".to_string(),
+        msg_class: "BAPI".to_string(),
+        msg_number: "001".to_string(),
+        message: "Update terminated".to_string(),
+    };
+    assert_eq!(e.code(), None);
+    assert_eq!(e.class(), ErrorClass::Application);
+    assert_eq!(e.to_string(), "BAPI error E BAPI/001: Update terminated");
+}
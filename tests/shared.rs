@@ -0,0 +1,25 @@
+use librfc_rust::{connection::Connection, shared::SharedConnection};
+
+#[test]
+fn call_from_threads() {
+    let c = SharedConnection::connect(Connection::new().destination("sap")).unwrap();
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let c = c.clone();
+            std::thread::spawn(move || c.call("RFC_SYSTEM_INFO", &serde_json::json!({})).unwrap())
+        })
+        .collect();
+    for h in handles {
+        let result = h.join().unwrap();
+        assert!(result.get("RFCSI_EXPORT").is_some());
+    }
+    c.ping().unwrap();
+    let attributes = c.with(|c| c.attributes()).unwrap();
+    assert!(!attributes.sys_id.is_empty());
+}
+
+#[test]
+fn is_send_sync_clone() {
+    fn assert_send_sync_clone<T: Send + Sync + Clone>() {}
+    assert_send_sync_clone::<SharedConnection>();
+}
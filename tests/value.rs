@@ -0,0 +1,91 @@
+use std::str::FromStr;
+
+use librfc_rust::value::{
+    format_utclong, parse_utclong, BytesEncoding, DateTime, Decimal, NaiveDate, NaiveTime,
+    SerializeWith, Value,
+};
+
+#[test]
+fn serialize_typed_values() {
+    let date = Value::from(NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+    let time = Value::from(NaiveTime::from_hms_opt(12, 30, 5).unwrap());
+    let amount = Value::from(Decimal::new(-12345, 2));
+    let values = vec![
+        date,
+        time,
+        amount,
+        Value::from(vec![0xde, 0xad]),
+        Value::from(true),
+    ];
+    assert_eq!(
+        serde_json::to_string(&values).unwrap(),
+        r#"["2024-02-29","12:30:05","-123.45","3q0=",true]"#
+    );
+}
+
+#[test]
+fn accessors() {
+    assert_eq!(Value::from("X").as_bool(), Some(true));
+    assert_eq!(Value::from(" ").as_bool(), Some(false));
+    assert_eq!(Value::from("001").as_i64(), Some(1));
+    assert_eq!(Value::from(Decimal::new(150, 2)).as_f64(), Some(1.5));
+    assert_eq!(Value::from(7).as_decimal(), Some(Decimal::from(7)));
+    let ts = Value::from(Decimal::new(202402291230001, 1))
+        .as_timestamp()
+        .unwrap();
+    assert_eq!(ts.to_rfc3339(), "2024-02-29T12:30:00.100+00:00");
+    assert!(Value::Empty.as_date().is_none());
+}
+
+#[test]
+fn bytes_encoding() {
+    let bytes = [0x25, 0x50, 0x44, 0x46, 0x00, 0xff];
+    assert_eq!(BytesEncoding::Hex.encode(&bytes), "2550444600FF");
+    assert_eq!(BytesEncoding::Hex.decode("2550444600ff").unwrap(), bytes);
+    let base64 = BytesEncoding::Base64.encode(&bytes);
+    assert_eq!(BytesEncoding::Base64.decode(&base64).unwrap(), bytes);
+    assert!(BytesEncoding::Hex.decode("ABC").is_err());
+    assert!(BytesEncoding::Hex.decode("ZZ").is_err());
+    assert!(BytesEncoding::Base64.decode("not base64!").is_err());
+}
+
+#[test]
+fn serialize_bytes_with_encoding() {
+    let value = Value::from(vec![0x25, 0x50, 0x44, 0x46]);
+    assert_eq!(serde_json::to_string(&value).unwrap(), r#""JVBERg==""#);
+    assert_eq!(
+        serde_json::to_string(&value.with_bytes_encoding(BytesEncoding::Hex)).unwrap(),
+        r#""25504446""#
+    );
+    assert_eq!(serde_json::to_string(&value).unwrap(), r#""JVBERg==""#);
+}
+
+#[test]
+fn serialize_exact_decimal() {
+    let d = Decimal::from_str("123456789012345678.123456789").unwrap();
+    let json = serde_json::to_string(&Value::from(d)).unwrap();
+    let s: String = serde_json::from_str(&json).unwrap();
+    assert_eq!(Decimal::from_str(&s).unwrap(), d);
+}
+
+#[test]
+fn utclong_format() {
+    let t = DateTime::parse_from_rfc3339("2024-02-29T12:30:05.123456789Z").unwrap();
+    let s = format_utclong(&t.to_utc());
+    assert_eq!(s, "2024-02-29T12:30:05,1234567");
+    assert_eq!(
+        parse_utclong(&s).unwrap().to_rfc3339(),
+        "2024-02-29T12:30:05.123456700+00:00"
+    );
+    let t = DateTime::parse_from_rfc3339("2024-02-29T00:00:00Z").unwrap();
+    assert_eq!(format_utclong(&t.to_utc()), "2024-02-29T00:00:00,0000000");
+}
+
+#[test]
+fn object_value() {
+    let object = serde_json::json!({ "class": "CL_NODE", "attributes": { "NAME": "root" } });
+    let value = Value::Object(object.as_object().unwrap().clone());
+    assert_eq!(serde_json::to_value(&value).unwrap(), object);
+    assert_eq!(value.to_string(), object.to_string());
+    assert!(value.as_object().is_some());
+}